@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use cozy_chess::*;
+use cozy_chess_pgn::*;
+use glowfish_engine::parse_book;
+
+const USAGE: &str = "\
+Generate an opening book for glowfish from a PGN collection.
+
+Usage: glowfish-bookgen <games.pgn> [options]
+
+Options:
+    --plies <n>       Only use the first n plies of each game [default: 12]
+    --min-games <n>   Only keep moves played in at least n games [default: 5]
+    --min-score <x>   Only keep moves scoring at least x for the mover, from 0 to 1 [default: 0.4]
+    --output <path>   Write the book to a file instead of stdout";
+
+#[derive(Debug, Clone, Copy)]
+struct Options {
+    plies: usize,
+    min_games: u32,
+    min_score: f64
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            plies: 12,
+            min_games: 5,
+            min_score: 0.4
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct MoveStats {
+    games: u32,
+    wins: u32,
+    draws: u32
+}
+
+impl MoveStats {
+    fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games as f64
+    }
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    positions: HashMap<u64, HashMap<Move, MoveStats>>,
+    games: usize,
+    skipped: usize
+}
+
+impl Stats {
+    fn add_game(&mut self, game: &Game, plies: usize) {
+        //Books are keyed from the standard start position.
+        if game.board != Board::default() {
+            self.skipped += 1;
+            return;
+        }
+        self.games += 1;
+        for (board, mv) in game.mainline_positions().take(plies) {
+            let stats = self.positions
+                .entry(board.hash())
+                .or_default()
+                .entry(mv)
+                .or_default();
+            stats.games += 1;
+            match (game.result, board.side_to_move()) {
+                (GameResult::WhiteWins, Color::White) | (GameResult::BlackWins, Color::Black) => stats.wins += 1,
+                (GameResult::Drawn, _) => stats.draws += 1,
+                _ => {}
+            }
+        }
+    }
+
+    //Moves that pass the filters, weighted by frequency relative to the most played move.
+    fn book_moves(&self, board: &Board, options: &Options) -> Vec<(Move, u32)> {
+        let moves = match self.positions.get(&board.hash()) {
+            Some(moves) => moves,
+            None => return Vec::new()
+        };
+        let mut kept: Vec<_> = moves.iter()
+            .filter(|(_, stats)| stats.games >= options.min_games && stats.score() >= options.min_score)
+            .map(|(&mv, stats)| (mv, stats.games))
+            .collect();
+        let most_played = kept.iter().map(|&(_, games)| games).max().unwrap_or(1);
+        for (_, weight) in &mut kept {
+            *weight = (*weight * 100 / most_played).max(1);
+        }
+        kept.sort_by(|(mv_a, weight_a), (mv_b, weight_b)| {
+            weight_b.cmp(weight_a).then_with(|| mv_a.to_string().cmp(&mv_b.to_string()))
+        });
+        kept
+    }
+
+    //Walks the book from the start position so that every written position is reachable.
+    fn write_book(&self, options: &Options) -> String {
+        let mut book = String::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::from([Board::default()]);
+        while let Some(board) = queue.pop_front() {
+            if !visited.insert(board.hash()) {
+                continue;
+            }
+            let moves = self.book_moves(&board, options);
+            if moves.is_empty() {
+                continue;
+            }
+            let moves: Vec<_> = moves.into_iter()
+                .map(|(mv, weight)| {
+                    let mut child = board.clone();
+                    child.play_unchecked(mv);
+                    queue.push_back(child);
+                    format!("{}:{}", board.to_standard_castle(mv), weight)
+                })
+                .collect();
+            writeln!(&mut book, "{}|{}", board, moves.join(",")).unwrap();
+        }
+        book
+    }
+}
+
+fn parse_args() -> Result<(String, Options, Option<String>), String> {
+    let mut args = std::env::args().skip(1);
+    let mut path = None;
+    let mut output = None;
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {}", name));
+        match arg.as_str() {
+            "--plies" => options.plies = value(&arg)?.parse().map_err(|_| "Invalid --plies")?,
+            "--min-games" => options.min_games = value(&arg)?.parse().map_err(|_| "Invalid --min-games")?,
+            "--min-score" => options.min_score = value(&arg)?.parse().map_err(|_| "Invalid --min-score")?,
+            "--output" => output = Some(value(&arg)?),
+            "--help" | "-h" => return Err(USAGE.to_owned()),
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE))
+        }
+    }
+    let path = path.ok_or_else(|| USAGE.to_owned())?;
+    Ok((path, options, output))
+}
+
+fn main() {
+    let (path, options, output) = parse_args().unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        std::process::exit(2);
+    });
+    let pgn = std::fs::read_to_string(&path).unwrap_or_else(|error| {
+        eprintln!("Failed to read {}: {}", path, error);
+        std::process::exit(1);
+    });
+
+    let mut stats = Stats::default();
+    for game in GameReader::new(&pgn) {
+        match game {
+            Ok(game) => stats.add_game(&game, options.plies),
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                std::process::exit(1);
+            }
+        }
+    }
+    let book = format!(
+        "# Generated by glowfish-bookgen from {} with --plies {} --min-games {} --min-score {}\n{}",
+        path,
+        options.plies,
+        options.min_games,
+        options.min_score,
+        stats.write_book(&options)
+    );
+    //Read the book back the way the cart's build script will.
+    let positions = parse_book(&book)
+        .unwrap_or_else(|error| panic!("Generated an invalid book at line {}", error))
+        .len();
+    eprintln!(
+        "Read {} games ({} skipped), wrote {} positions",
+        stats.games,
+        stats.skipped,
+        positions
+    );
+    match output {
+        Some(output) => std::fs::write(&output, book).unwrap_or_else(|error| {
+            eprintln!("Failed to write {}: {}", output, error);
+            std::process::exit(1);
+        }),
+        None => print!("{}", book)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PGN: &str = "\
+[Result \"1-0\"]
+1. e4 e5 2. Nf3 Nc6 1-0
+
+[Result \"1/2-1/2\"]
+1. e4 e5 2. Nf3 Nf6 1/2-1/2
+
+[Result \"0-1\"]
+1. e4 c5 2. Nf3 0-1
+
+[Result \"1-0\"]
+1. d4 d5 1-0
+
+[FEN \"4k3/8/8/8/8/8/8/4K3 w - - 0 1\"]
+[SetUp \"1\"]
+[Result \"*\"]
+1. Ke2 *
+";
+
+    fn generate(options: &Options) -> (Stats, String) {
+        let mut stats = Stats::default();
+        for game in GameReader::new(PGN) {
+            stats.add_game(&game.unwrap(), options.plies);
+        }
+        let book = stats.write_book(options);
+        (stats, book)
+    }
+
+    #[test]
+    fn counts_moves_and_results() {
+        let options = Options {
+            plies: 3,
+            min_games: 1,
+            min_score: 0.0
+        };
+        let (stats, book) = generate(&options);
+        assert_eq!(stats.games, 4);
+        assert_eq!(stats.skipped, 1);
+        let start = &stats.positions[&Board::default().hash()];
+        let e4 = start[&"e2e4".parse().unwrap()];
+        assert_eq!((e4.games, e4.wins, e4.draws), (3, 1, 1));
+        let first = book.lines().next().unwrap();
+        assert_eq!(first, format!("{}|e2e4:100,d2d4:33", Board::default()));
+        //Plies past the limit are ignored.
+        assert!(!book.contains("b8c6"));
+    }
+
+    #[test]
+    fn filters_keep_book_reachable() {
+        let options = Options {
+            plies: 12,
+            min_games: 2,
+            min_score: 0.5
+        };
+        let (_, book) = generate(&options);
+        //1. e4 scores 0.5 over 3 games, but Black's replies are each below the thresholds.
+        assert_eq!(book, format!("{}|e2e4:100\n", Board::default()));
+        let book = parse_book(&book).unwrap();
+        assert_eq!(book[&Board::default().hash()].moves, [("e2e4".parse().unwrap(), 100)]);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::*;
+
+impl Board {
+    /// Get all pieces of a color that attack a square, using `occupied` as the blockers.
+    /// Pieces not in `occupied` are treated as removed, both as blockers and as attackers.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/8/8/3p4/8/1B3N2/8/3RK3 w - - 0 1".parse().unwrap();
+    /// let attackers = board.attackers(Square::D5, Color::White, board.occupied());
+    /// assert_eq!(attackers, Square::B3.bitboard() | Square::D1.bitboard());
+    /// let occupied = board.occupied() ^ Square::B3.bitboard() ^ Square::F3.bitboard();
+    /// let attackers = board.attackers(Square::D5, Color::White, occupied);
+    /// assert_eq!(attackers, Square::D1.bitboard());
+    /// ```
+    pub fn attackers(&self, square: Square, color: Color, occupied: BitBoard) -> BitBoard {
+        let bishops = self.pieces(Piece::Bishop) | self.pieces(Piece::Queen);
+        let rooks = self.pieces(Piece::Rook) | self.pieces(Piece::Queen);
+        let attackers =
+            (get_bishop_moves(square, occupied) & bishops) |
+            (get_rook_moves(square, occupied) & rooks) |
+            (get_knight_moves(square) & self.pieces(Piece::Knight)) |
+            (get_king_moves(square) & self.pieces(Piece::King)) |
+            (get_pawn_attacks(square, !color) & self.pieces(Piece::Pawn));
+        attackers & self.colors(color) & occupied
+    }
+
+    /// Check if a square is attacked by any piece of a color.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/8/8/3p4/8/1B3N2/8/3RK3 w - - 0 1".parse().unwrap();
+    /// assert!(board.is_attacked(Square::E4, Color::Black));
+    /// assert!(board.is_attacked(Square::H4, Color::White));
+    /// assert!(!board.is_attacked(Square::H5, Color::White));
+    /// ```
+    pub fn is_attacked(&self, square: Square, color: Color) -> bool {
+        !self.attackers(square, color, self.occupied()).is_empty()
+    }
+
+    /// Get all squares attacked by a color.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/8/8/3p4/8/1B3N2/8/3RK3 w - - 0 1".parse().unwrap();
+    /// assert_eq!(board.attacks(Color::Black), bitboard! {
+    ///     . . . X . X . .
+    ///     . . . X X X . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . X . X . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    ///     . . . . . . . .
+    /// });
+    /// ```
+    pub fn attacks(&self, color: Color) -> BitBoard {
+        let occupied = self.occupied();
+        let pieces = self.colors(color);
+        let mut attacks = BitBoard::EMPTY;
+        for square in pieces & self.pieces(Piece::Pawn) {
+            attacks |= get_pawn_attacks(square, color);
+        }
+        for square in pieces & self.pieces(Piece::Knight) {
+            attacks |= get_knight_moves(square);
+        }
+        for square in pieces & (self.pieces(Piece::Bishop) | self.pieces(Piece::Queen)) {
+            attacks |= get_bishop_moves(square, occupied);
+        }
+        for square in pieces & (self.pieces(Piece::Rook) | self.pieces(Piece::Queen)) {
+            attacks |= get_rook_moves(square, occupied);
+        }
+        for square in pieces & self.pieces(Piece::King) {
+            attacks |= get_king_moves(square);
+        }
+        attacks
+    }
+
+    /// Get the sliders of a color that would attack a square
+    /// if the piece on `through` was removed, but don't currently attack it.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/8/8/3p4/8/1B6/Q7/3RK3 w - - 0 1".parse().unwrap();
+    /// let xrays = board.xray_attackers(Square::D5, Color::White, Square::B3);
+    /// assert_eq!(xrays, Square::A2.bitboard());
+    /// let xrays = board.xray_attackers(Square::D5, Color::White, Square::D1);
+    /// assert_eq!(xrays, BitBoard::EMPTY);
+    /// ```
+    pub fn xray_attackers(&self, square: Square, color: Color, through: Square) -> BitBoard {
+        let occupied = self.occupied();
+        let revealed = self.attackers(square, color, occupied & !through.bitboard());
+        revealed & !self.attackers(square, color, occupied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece_attacks(board: &Board, square: Square) -> BitBoard {
+        let color = board.color_on(square).unwrap();
+        let occupied = board.occupied();
+        match board.piece_on(square).unwrap() {
+            Piece::Pawn => get_pawn_attacks(square, color),
+            Piece::Knight => get_knight_moves(square),
+            Piece::Bishop => get_bishop_moves(square, occupied),
+            Piece::Rook => get_rook_moves(square, occupied),
+            Piece::Queen => get_bishop_moves(square, occupied) | get_rook_moves(square, occupied),
+            Piece::King => get_king_moves(square)
+        }
+    }
+
+    #[test]
+    fn attack_queries() {
+        for fen in include_str!("test_data/valid.sfens").lines().take(500) {
+            let board = Board::from_fen(fen, true).unwrap();
+            for &color in &Color::ALL {
+                let mut attacks = BitBoard::EMPTY;
+                for square in board.colors(color) {
+                    attacks |= piece_attacks(&board, square);
+                }
+                assert_eq!(board.attacks(color), attacks, "{}", fen);
+                for square in BitBoard::FULL {
+                    let attackers = board.colors(color)
+                        .into_iter()
+                        .filter(|&attacker| piece_attacks(&board, attacker).has(square))
+                        .fold(BitBoard::EMPTY, |bb, attacker| bb | attacker.bitboard());
+                    assert_eq!(board.attackers(square, color, board.occupied()), attackers, "{}", fen);
+                    assert_eq!(board.is_attacked(square, color), attacks.has(square), "{}", fen);
+                }
+            }
+            let king = board.king(board.side_to_move());
+            let checkers = board.attackers(king, !board.side_to_move(), board.occupied());
+            assert_eq!(checkers, board.checkers(), "{}", fen);
+        }
+    }
+}
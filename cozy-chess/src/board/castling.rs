@@ -0,0 +1,154 @@
+use crate::*;
+
+impl Board {
+    fn standard_castle_king(&self, mv: Move) -> Option<Rank> {
+        let color = self.side_to_move();
+        let back_rank = Rank::First.relative_to(color);
+        let our_king = self.pieces(Piece::King) & self.colors(color);
+        let is_castle_candidate = our_king.has(mv.from)
+            && mv.from == Square::new(File::E, back_rank)
+            && mv.to.rank() == back_rank
+            && mv.promotion.is_none();
+        if is_castle_candidate {
+            Some(back_rank)
+        } else {
+            None
+        }
+    }
+
+    /// Convert a castling move in standard notation (king moves two squares, e.g. `e1g1`)
+    /// to the king captures rook notation used by [`Board`] (e.g. `e1h1`).
+    /// Standard notation is only defined for kings on the e-file, which includes
+    /// Chess960 positions where the king starts on the e-file.
+    /// Any other move, including moves already in king captures rook notation, is returned unchanged.
+    /// This does not check the legality of the move.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// let convert = |mv: &str| board.to_king_rook_castle(mv.parse().unwrap()).to_string();
+    /// assert_eq!(convert("e1g1"), "e1h1");
+    /// assert_eq!(convert("e1c1"), "e1a1");
+    /// assert_eq!(convert("e1h1"), "e1h1");
+    /// assert_eq!(convert("e1d1"), "e1d1");
+    /// ```
+    pub fn to_king_rook_castle(&self, mv: Move) -> Move {
+        if let Some(back_rank) = self.standard_castle_king(mv) {
+            let rights = self.castle_rights(self.side_to_move());
+            let rook = match mv.to.file() {
+                File::G => rights.short,
+                File::C => rights.long,
+                _ => None
+            };
+            if let Some(rook) = rook {
+                return Move {
+                    from: mv.from,
+                    to: Square::new(rook, back_rank),
+                    promotion: None
+                };
+            }
+        }
+        mv
+    }
+
+    /// Convert a castling move in the king captures rook notation used by [`Board`] (e.g. `e1h1`)
+    /// to standard notation (king moves two squares, e.g. `e1g1`).
+    /// Standard notation is only defined for kings on the e-file;
+    /// castling moves for kings on other files are returned unchanged.
+    /// Any other move is returned unchanged.
+    /// This does not check the legality of the move.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// let convert = |mv: &str| board.to_standard_castle(mv.parse().unwrap()).to_string();
+    /// assert_eq!(convert("e1h1"), "e1g1");
+    /// assert_eq!(convert("e1a1"), "e1c1");
+    /// assert_eq!(convert("e1d1"), "e1d1");
+    /// ```
+    pub fn to_standard_castle(&self, mv: Move) -> Move {
+        if let Some(back_rank) = self.standard_castle_king(mv) {
+            let color = self.side_to_move();
+            let rights = self.castle_rights(color);
+            let is_rook = self.colors(color).has(mv.to);
+            let king_dest = if !is_rook {
+                None
+            } else if Some(mv.to.file()) == rights.short {
+                Some(File::G)
+            } else if Some(mv.to.file()) == rights.long {
+                Some(File::C)
+            } else {
+                None
+            };
+            if let Some(king_dest) = king_dest {
+                return Move {
+                    from: mv.from,
+                    to: Square::new(king_dest, back_rank),
+                    promotion: None
+                };
+            }
+        }
+        mv
+    }
+
+    /// Parse a move in UCI notation, accepting castling moves in both standard
+    /// and king captures rook notation. The move is returned in king captures rook notation.
+    /// See [`Board::to_king_rook_castle`].
+    /// This does not check the legality of the move.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let mut board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// assert_eq!(board.parse_move("e1g1").unwrap(), "e1h1".parse().unwrap());
+    /// assert_eq!(board.parse_move("e1h1").unwrap(), "e1h1".parse().unwrap());
+    /// board.play(board.parse_move("e1c1").unwrap());
+    /// board.play(board.parse_move("e8g8").unwrap());
+    /// const EXPECTED: &str = "r4rk1/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/2KR3R w - - 2 2";
+    /// assert_eq!(format!("{}", board), EXPECTED);
+    /// ```
+    pub fn parse_move(&self, mv: &str) -> Result<Move, MoveParseError> {
+        mv.parse().map(|mv| self.to_king_rook_castle(mv))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_conversions(board: &Board) {
+        board.generate_moves(|moves| {
+            for mv in moves {
+                let standard = board.to_standard_castle(mv);
+                assert_eq!(board.to_king_rook_castle(standard), mv, "{} {}", board, mv);
+                assert_eq!(board.parse_move(&standard.to_string()).unwrap(), mv);
+                let is_castle = board.colors(board.side_to_move()).has(mv.to);
+                if standard != mv {
+                    assert!(is_castle);
+                    assert_eq!(mv.from.file(), File::E);
+                    assert!(matches!(standard.to.file(), File::G | File::C));
+                } else if is_castle {
+                    assert!(mv.from.file() != File::E || matches!(mv.to.file(), File::G | File::C));
+                }
+            }
+            false
+        });
+    }
+
+    #[test]
+    fn castle_conversions() {
+        const FENS: &[&str] = &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w HAha - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b HAha - 0 1",
+            "rq1kr3/p1ppbp1p/bpn3pB/3Np3/3P4/1P1Q1Nn1/P1P1BPPP/R2KR3 w AEae - 3 15",
+            "rk2r3/pqppbp1p/bpn3pB/3Npn2/3P4/1P1Q1N2/P1P2PPP/RKRB4 w ACa - 3 15",
+            "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1",
+            "2r1kr2/8/8/8/8/8/8/2R1KR2 b FCfc - 0 1"
+        ];
+        for fen in FENS {
+            test_conversions(&Board::from_fen(fen, true).unwrap());
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::*;
+
+impl Board {
+    /// Check if a legal move gives check, without playing it.
+    /// This includes discovered checks, checks by promoted pieces,
+    /// checks discovered by en passant captures and checks by the rook when castling.
+    /// Note that this only supports Chess960 style castling.
+    /// # Panics
+    /// This may panic if the board or move is invalid. However, this is not guaranteed.
+    /// See [`Board::try_gives_check`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "3k4/1P6/8/8/8/8/2R5/R3K3 w Q - 0 1".parse().unwrap();
+    /// assert!(board.gives_check("c2d2".parse().unwrap()));
+    /// assert!(!board.gives_check("c2c7".parse().unwrap()));
+    /// // Promotions
+    /// assert!(board.gives_check("b7b8q".parse().unwrap()));
+    /// assert!(!board.gives_check("b7b8n".parse().unwrap()));
+    /// // Castling rook check
+    /// assert!(board.gives_check("e1a1".parse().unwrap()));
+    /// ```
+    pub fn gives_check(&self, mv: Move) -> bool {
+        self.try_gives_check(mv).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::gives_check`].
+    /// # Errors
+    /// See [`Board::gives_check`]'s panics.
+    pub fn try_gives_check(&self, mv: Move) -> Result<bool, BoardError> {
+        let color = self.side_to_move();
+        let their_king = self.try_king(!color)?;
+        let moved = self.piece_on(mv.from).ok_or(BoardError::InvalidBoard)?;
+        let from_bb = mv.from.bitboard();
+        let to_bb = mv.to.bitboard();
+
+        if self.colors(color).has(mv.to) {
+            //Castling move encoded as king captures rook.
+            let back_rank = Rank::First.relative_to(color);
+            let (king, rook) = if mv.from.file() < mv.to.file() {
+                (File::G, File::F)
+            } else {
+                (File::C, File::D)
+            };
+            let king = Square::new(king, back_rank);
+            let rook = Square::new(rook, back_rank);
+            let occupied = self.occupied() ^ from_bb ^ to_bb | king.bitboard() | rook.bitboard();
+            let discovered = self.attackers(their_king, color, occupied) & !(from_bb | to_bb);
+            return Ok(get_rook_moves(rook, occupied).has(their_king) || !discovered.is_empty());
+        }
+
+        let mut occupied = self.occupied() ^ from_bb | to_bb;
+        let en_passant = self.en_passant()
+            .map(|file| Square::new(file, Rank::Sixth.relative_to(color)));
+        if moved == Piece::Pawn && Some(mv.to) == en_passant {
+            let victim_square = Square::new(mv.to.file(), Rank::Fifth.relative_to(color));
+            occupied ^= victim_square.bitboard();
+        }
+
+        let direct = match mv.promotion.unwrap_or(moved) {
+            Piece::Pawn => get_pawn_attacks(mv.to, color),
+            Piece::Knight => get_knight_moves(mv.to),
+            Piece::Bishop => get_bishop_moves(mv.to, occupied),
+            Piece::Rook => get_rook_moves(mv.to, occupied),
+            Piece::Queen => get_bishop_moves(mv.to, occupied) | get_rook_moves(mv.to, occupied),
+            Piece::King => BitBoard::EMPTY
+        };
+        //The moved piece is no longer on its source square, so this only finds discovered checks.
+        let discovered = self.attackers(their_king, color, occupied);
+        Ok(direct.has(their_king) || !discovered.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_check_matches_play() {
+        for fen in include_str!("test_data/valid.sfens").lines().take(2000) {
+            let board = Board::from_fen(fen, true).unwrap();
+            board.generate_moves(|moves| {
+                for mv in moves {
+                    let mut child = board.clone();
+                    child.play_unchecked(mv);
+                    let check = !child.checkers().is_empty();
+                    assert_eq!(board.gives_check(mv), check, "{} {}", fen, mv);
+                }
+                false
+            });
+        }
+    }
+
+    #[test]
+    fn special_checks() {
+        const TESTS: &[(&str, &str, bool)] = &[
+            //En passant discovered check
+            ("8/8/8/k2pP2R/8/8/8/4K3 w - d6 0 1", "e5d6", true),
+            ("8/6k1/8/4pP2/8/8/8/B2K4 w - e6 0 1", "f5e6", true),
+            ("8/8/3k4/4pP2/8/8/8/3K4 w - e6 0 1", "f5e6", false),
+            //Promotions
+            ("3k4/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", true),
+            ("3k4/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8b", false),
+            ("8/P2k4/8/8/8/8/8/4K3 w - - 0 1", "a7a8n", false),
+            ("8/P7/1k6/8/8/8/8/4K3 w - - 0 1", "a7a8n", true),
+            //Castling
+            ("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1h1", true),
+            ("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1a1", true),
+            ("2k5/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1a1", false)
+        ];
+        for &(fen, mv, expected) in TESTS {
+            let board: Board = fen.parse().unwrap();
+            let mv = mv.parse().unwrap();
+            assert!(board.is_legal(mv), "{} {}", fen, mv);
+            assert_eq!(board.gives_check(mv), expected, "{} {}", fen, mv);
+            let mut child = board.clone();
+            child.play_unchecked(mv);
+            assert_eq!(!child.checkers().is_empty(), expected, "{} {}", fen, mv);
+        }
+    }
+}
@@ -5,10 +5,22 @@ mod parse;
 mod zobrist;
 mod builder;
 mod validate;
+mod san;
+mod castling;
+mod undo;
+mod attacks;
+mod see;
+mod check;
+mod packed;
 
 use zobrist::*;
 pub use movegen::*;
+pub use parse::*;
 pub use builder::*;
+pub use san::*;
+pub use undo::*;
+pub use see::*;
+pub use packed::*;
 
 /// The current state of the game.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -437,6 +449,7 @@ impl Board {
     }
 
     /// Play a move while checking its legality. Note that this only supports Chess960 style castling.
+    /// See [`Board::to_king_rook_castle`] to convert moves with standard castling.
     /// # Panics
     /// This is guaranteed to panic if the move is illegal.
     /// This may panic if the board is invalid. However, this is not guaranteed.
@@ -466,6 +479,7 @@ impl Board {
     }
 
     /// Play a move without checking its legality. Note that this only supports Chess960 style castling.
+    /// See [`Board::to_king_rook_castle`] to convert moves with standard castling.
     /// # Panics
     /// This may panic if the board or move is invalid. However, this is not guaranteed.
     /// See [`Board::try_play_unchecked`] for a non-panicking variant.
@@ -40,6 +40,26 @@ mod slider {
     }
 }
 
+//Restricts generated moves by source and destination square.
+//Pawns get their own destination mask so that promotions and
+//en passant can be counted as tactical moves.
+#[derive(Clone, Copy)]
+struct MoveMasks {
+    from: BitBoard,
+    to: BitBoard,
+    pawn_to: BitBoard
+}
+
+impl MoveMasks {
+    fn all(from: BitBoard) -> Self {
+        Self {
+            from,
+            to: BitBoard::FULL,
+            pawn_to: BitBoard::FULL
+        }
+    }
+}
+
 macro_rules! abort_if {
     ($($expr:expr),*) => {
         $(if $expr {
@@ -66,13 +86,13 @@ impl Board {
 
     fn add_slider_legals<
         P: slider::SlidingPiece, F: FnMut(PieceMoves) -> bool, const IN_CHECK: bool
-    >(&self, mask: BitBoard, listener: &mut F) -> bool {
+    >(&self, masks: MoveMasks, listener: &mut F) -> bool {
         let color = self.side_to_move();
         let our_king = self.king(color);
-        let pieces = self.pieces(P::PIECE) & self.colors(color) & mask;
+        let pieces = self.pieces(P::PIECE) & self.colors(color) & masks.from;
         let pinned = self.pinned();
         let blockers = self.occupied();
-        let target_squares = self.target_squares::<IN_CHECK>();
+        let target_squares = self.target_squares::<IN_CHECK>() & masks.to;
 
         for piece in pieces & !pinned {
             let moves = P::pseudo_legals(piece, blockers) & target_squares;
@@ -104,13 +124,13 @@ impl Board {
 
     fn add_knight_legals<
         F: FnMut(PieceMoves) -> bool, const IN_CHECK: bool
-    >(&self, mask: BitBoard, listener: &mut F) -> bool {
+    >(&self, masks: MoveMasks, listener: &mut F) -> bool {
         const PIECE: Piece = Piece::Knight;
 
         let color = self.side_to_move();
-        let pieces = self.pieces(PIECE) & self.colors(color) & mask;
+        let pieces = self.pieces(PIECE) & self.colors(color) & masks.from;
         let pinned = self.pinned();
-        let target_squares = self.target_squares::<IN_CHECK>();
+        let target_squares = self.target_squares::<IN_CHECK>() & masks.to;
 
         for piece in pieces & !pinned {
             let moves = get_knight_moves(piece) & target_squares;
@@ -127,16 +147,16 @@ impl Board {
 
     fn add_pawn_legals<
         F: FnMut(PieceMoves) -> bool, const IN_CHECK: bool
-    >(&self, mask: BitBoard, listener: &mut F) -> bool {
+    >(&self, masks: MoveMasks, listener: &mut F) -> bool {
         const PIECE: Piece = Piece::Pawn;
 
         let color = self.side_to_move();
         let our_king = self.king(color);
-        let pieces = self.pieces(PIECE) & self.colors(color) & mask;
+        let pieces = self.pieces(PIECE) & self.colors(color) & masks.from;
         let their_pieces = self.colors(!color);
         let pinned = self.pinned();
         let blockers = self.occupied();
-        let target_squares = self.target_squares::<IN_CHECK>();
+        let target_squares = self.target_squares::<IN_CHECK>() & masks.pawn_to;
 
         for piece in pieces & !pinned {
             let moves = (
@@ -182,6 +202,11 @@ impl Board {
 
             let dest = Square::new(en_passant, Rank::Third.relative_to(!color));
             let victim = Square::new(en_passant, Rank::Fourth.relative_to(!color));
+            let pieces = if masks.pawn_to.has(dest) {
+                pieces
+            } else {
+                BitBoard::EMPTY
+            };
             for piece in get_pawn_attacks(dest, !color) & pieces {
                 //Simulate the capture and update the pieces accordingly.
                 let blockers = blockers
@@ -257,17 +282,17 @@ impl Board {
 
     fn add_king_legals<
         F: FnMut(PieceMoves) -> bool, const IN_CHECK: bool
-    >(&self, mask: BitBoard, listener: &mut F) -> bool {
+    >(&self, masks: MoveMasks, listener: &mut F) -> bool {
         const PIECE: Piece = Piece::King;
 
         let color = self.side_to_move();
         let our_pieces = self.colors(color);
         let our_king = self.king(color);
-        if !mask.has(our_king) {
+        if !masks.from.has(our_king) {
             return false;
         }
         let mut moves = BitBoard::EMPTY;
-        for to in get_king_moves(our_king) & !our_pieces {
+        for to in get_king_moves(our_king) & !our_pieces & masks.to {
             if self.king_safe_on(to) {
                 moves |= to.bitboard();
             }
@@ -276,13 +301,15 @@ impl Board {
             let rights = self.castle_rights(color);
             let back_rank = Rank::First.relative_to(color);
             if let Some(rook) = rights.short {
-                if self.can_castle(rook, File::G, File::F) {
-                    moves |= Square::new(rook, back_rank).bitboard();
+                let rook_square = Square::new(rook, back_rank);
+                if masks.to.has(rook_square) && self.can_castle(rook, File::G, File::F) {
+                    moves |= rook_square.bitboard();
                 }
             }
             if let Some(rook) = rights.long {
-                if self.can_castle(rook, File::C, File::D) {
-                    moves |= Square::new(rook, back_rank).bitboard();
+                let rook_square = Square::new(rook, back_rank);
+                if masks.to.has(rook_square) && self.can_castle(rook, File::C, File::D) {
+                    moves |= rook_square.bitboard();
                 }
             }
         }
@@ -298,14 +325,14 @@ impl Board {
 
     fn add_all_legals<
         F: FnMut(PieceMoves) -> bool, const IN_CHECK: bool
-    >(&self, mask: BitBoard, listener: &mut F) -> bool {
+    >(&self, masks: MoveMasks, listener: &mut F) -> bool {
         abort_if! {
-            self.add_pawn_legals::<_, IN_CHECK>(mask, listener),
-            self.add_knight_legals::<_, IN_CHECK>(mask, listener),
-            self.add_slider_legals::<slider::Bishop, _, IN_CHECK>(mask, listener),
-            self.add_slider_legals::<slider::Rook, _, IN_CHECK>(mask, listener),
-            self.add_slider_legals::<slider::Queen, _, IN_CHECK>(mask, listener),
-            self.add_king_legals::<_, IN_CHECK>(mask, listener)
+            self.add_pawn_legals::<_, IN_CHECK>(masks, listener),
+            self.add_knight_legals::<_, IN_CHECK>(masks, listener),
+            self.add_slider_legals::<slider::Bishop, _, IN_CHECK>(masks, listener),
+            self.add_slider_legals::<slider::Rook, _, IN_CHECK>(masks, listener),
+            self.add_slider_legals::<slider::Queen, _, IN_CHECK>(masks, listener),
+            self.add_king_legals::<_, IN_CHECK>(masks, listener)
         }
         false
     }
@@ -379,18 +406,198 @@ impl Board {
     /// See [`Board::generate_moves_for`]'s panics.
     pub fn try_generate_moves_for(
         &self, mask: BitBoard, mut listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        self.try_generate_masked(MoveMasks::all(mask), &mut listener)
+    }
+
+    fn try_generate_masked(
+        &self, masks: MoveMasks, listener: &mut impl FnMut(PieceMoves) -> bool
     ) -> Result<bool, BoardError> {
         if self.try_king(self.side_to_move()).is_err() {
             return Err(BoardError::InvalidBoard);
         }
         Ok(match self.checkers().popcnt() {
-            0 => self.add_all_legals::<_, false>(mask, &mut listener),
-            1 => self.add_all_legals::<_, true>(mask ,&mut listener),
-            _ => self.add_king_legals::<_, true>(mask, &mut listener)
+            0 => self.add_all_legals::<_, false>(masks, listener),
+            1 => self.add_all_legals::<_, true>(masks, listener),
+            _ => self.add_king_legals::<_, true>(masks, listener)
         })
     }
 
+    //Destinations of tactical moves: captures, en passant and promotions.
+    //Castling is encoded as capturing our own rook, so it is never tactical.
+    fn tactical_masks(&self, mask: BitBoard) -> MoveMasks {
+        let color = self.side_to_move();
+        let their_pieces = self.colors(!color);
+        let mut pawn_to = their_pieces | Rank::Eighth.relative_to(color).bitboard();
+        if let Some(en_passant) = self.en_passant() {
+            pawn_to |= Square::new(en_passant, Rank::Sixth.relative_to(color)).bitboard();
+        }
+        MoveMasks {
+            from: mask,
+            to: their_pieces,
+            pawn_to
+        }
+    }
+
+    /// Version of [`Board::generate_moves`] that generates only tactical moves.
+    /// Tactical moves are captures, en passant captures and promotions, including quiet
+    /// promotions and underpromotions. Castling is never a tactical move.
+    /// Together with [`Board::generate_quiet_moves`], this generates exactly the moves of
+    /// [`Board::generate_moves`], with the same legality guarantees.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_generate_tactical_moves`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/1P6/8/3p4/4P3/8/8/4K3 w - - 0 1".parse().unwrap();
+    /// let mut tactical_moves = 0;
+    /// board.generate_tactical_moves(|moves| {
+    ///     tactical_moves += moves.len();
+    ///     false
+    /// });
+    /// // exd5 and four promotions
+    /// assert_eq!(tactical_moves, 5);
+    /// ```
+    pub fn generate_tactical_moves(&self, listener: impl FnMut(PieceMoves) -> bool) -> bool {
+        self.generate_tactical_moves_for(BitBoard::FULL, listener)
+    }
+
+    /// Non-panicking version of [`Board::generate_tactical_moves`].
+    /// # Errors
+    /// See [`Board::generate_tactical_moves`]'s panics.
+    pub fn try_generate_tactical_moves(
+        &self, listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        self.try_generate_tactical_moves_for(BitBoard::FULL, listener)
+    }
+
+    /// Version of [`Board::generate_tactical_moves`] that
+    /// generates moves for only a subset of pieces.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_generate_tactical_moves_for`] for a non-panicking variant.
+    pub fn generate_tactical_moves_for(
+        &self, mask: BitBoard, listener: impl FnMut(PieceMoves) -> bool
+    ) -> bool {
+        self.try_generate_tactical_moves_for(mask, listener).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::generate_tactical_moves_for`].
+    /// # Errors
+    /// See [`Board::generate_tactical_moves_for`]'s panics.
+    pub fn try_generate_tactical_moves_for(
+        &self, mask: BitBoard, mut listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        self.try_generate_masked(self.tactical_masks(mask), &mut listener)
+    }
+
+    /// Version of [`Board::generate_moves`] that generates only quiet moves.
+    /// Quiet moves are all moves that aren't tactical as defined by
+    /// [`Board::generate_tactical_moves`], including castling.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_generate_quiet_moves`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/1P6/8/3p4/4P3/8/8/4K3 w - - 0 1".parse().unwrap();
+    /// let mut quiet_moves = 0;
+    /// board.generate_quiet_moves(|moves| {
+    ///     quiet_moves += moves.len();
+    ///     false
+    /// });
+    /// // e5 and five king moves
+    /// assert_eq!(quiet_moves, 6);
+    /// ```
+    pub fn generate_quiet_moves(&self, listener: impl FnMut(PieceMoves) -> bool) -> bool {
+        self.generate_quiet_moves_for(BitBoard::FULL, listener)
+    }
+
+    /// Non-panicking version of [`Board::generate_quiet_moves`].
+    /// # Errors
+    /// See [`Board::generate_quiet_moves`]'s panics.
+    pub fn try_generate_quiet_moves(
+        &self, listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        self.try_generate_quiet_moves_for(BitBoard::FULL, listener)
+    }
+
+    /// Version of [`Board::generate_quiet_moves`] that
+    /// generates moves for only a subset of pieces.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_generate_quiet_moves_for`] for a non-panicking variant.
+    pub fn generate_quiet_moves_for(
+        &self, mask: BitBoard, listener: impl FnMut(PieceMoves) -> bool
+    ) -> bool {
+        self.try_generate_quiet_moves_for(mask, listener).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::generate_quiet_moves_for`].
+    /// # Errors
+    /// See [`Board::generate_quiet_moves_for`]'s panics.
+    pub fn try_generate_quiet_moves_for(
+        &self, mask: BitBoard, mut listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        let tactical = self.tactical_masks(mask);
+        let masks = MoveMasks {
+            from: mask,
+            to: !tactical.to,
+            pawn_to: !tactical.pawn_to
+        };
+        self.try_generate_masked(masks, &mut listener)
+    }
+
+    /// Generate all legal check evasions.
+    /// If the side to move is in check, this generates exactly the moves of
+    /// [`Board::generate_moves`]. Otherwise, no moves are generated.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_generate_evasions`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "4k3/8/8/8/8/8/4r3/R3K3 w Q - 0 1".parse().unwrap();
+    /// let mut evasions = 0;
+    /// board.generate_evasions(|moves| {
+    ///     evasions += moves.len();
+    ///     false
+    /// });
+    /// // Kxe2, Kd1 and Kf1
+    /// assert_eq!(evasions, 3);
+    /// assert!(!Board::default().generate_evasions(|_| true));
+    /// ```
+    pub fn generate_evasions(&self, listener: impl FnMut(PieceMoves) -> bool) -> bool {
+        self.try_generate_evasions(listener).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::generate_evasions`].
+    /// # Errors
+    /// See [`Board::generate_evasions`]'s panics.
+    pub fn try_generate_evasions(
+        &self, mut listener: impl FnMut(PieceMoves) -> bool
+    ) -> Result<bool, BoardError> {
+        if self.checkers().is_empty() {
+            return self.try_king(self.side_to_move()).map(|_| false);
+        }
+        self.try_generate_masked(MoveMasks::all(BitBoard::FULL), &mut listener)
+    }
+
     fn king_is_legal(&self, mv: Move) -> bool {
+        if self.checkers().is_empty() && self.castle_is_legal(mv) {
+            return true;
+        }
+        if !(get_king_moves(mv.from) & !self.colors(self.side_to_move())).has(mv.to) {
+            return false;
+        }
+        if mv.promotion.is_some() {
+            return false;
+        }
+        self.king_safe_on(mv.to)
+    }
+
+    fn castle_is_legal(&self, mv: Move) -> bool {
         let castles = self.castle_rights(self.side_to_move());
         let back_rank = Rank::First.relative_to(self.side_to_move());
         if let Some(rook) = castles.short {
@@ -405,16 +612,14 @@ impl Board {
                 return true;
             }
         }
-        if !(get_king_moves(mv.from) & !self.colors(self.side_to_move())).has(mv.to) {
-            return false;
-        }
-        if mv.promotion.is_some() {
-            return false;
-        }
-        self.king_safe_on(mv.to)
+        false
     }
 
     /// See if a move is legal.
+    /// Any [`Move`] value may be checked, including moves from empty squares, moves with
+    /// invalid promotion pieces and castling moves without the corresponding rights.
+    /// This makes it suitable for validating untrusted moves, such as moves
+    /// retrieved from a transposition table that may be the result of a hash collision.
     /// # Panics
     /// This may panic if the board is invalid. However, this is not guaranteed.
     /// See [`Board::try_is_legal`] for a non-panicking variant.
@@ -424,6 +629,12 @@ impl Board {
     /// let mut board = Board::default();
     /// assert!(board.is_legal("e2e4".parse().unwrap()));
     /// assert!(!board.is_legal("e1e8".parse().unwrap()));
+    /// let mv = Move {
+    ///     from: Square::E2,
+    ///     to: Square::E4,
+    ///     promotion: Some(Piece::King)
+    /// };
+    /// assert!(!board.is_legal(mv));
     /// ```
     pub fn is_legal(&self, mv: Move) -> bool {
         self.try_is_legal(mv).expect("Invalid board!")
@@ -471,9 +682,9 @@ impl Board {
                 }
                 let mut c = |moves: PieceMoves| moves.to.has(mv.to);
                 if self.checkers().is_empty() {
-                    self.add_pawn_legals::<_, false>(mv.from.bitboard(), &mut c)
+                    self.add_pawn_legals::<_, false>(MoveMasks::all(mv.from.bitboard()), &mut c)
                 } else {
-                    self.add_pawn_legals::<_, true>(mv.from.bitboard(), &mut c)
+                    self.add_pawn_legals::<_, true>(MoveMasks::all(mv.from.bitboard()), &mut c)
                 }
             }
             Some(Piece::Rook) => {
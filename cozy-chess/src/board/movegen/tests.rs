@@ -179,6 +179,132 @@ fn subset_movegen_kiwipete() {
     visit(&board, 4);
 }
 
+fn staged_perft(board: &Board, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    let mut visit = |moves: PieceMoves| {
+        for mv in moves {
+            let mut board = board.clone();
+            board.play_unchecked(mv);
+            nodes += staged_perft(&board, depth - 1);
+        }
+        false
+    };
+    if board.checkers().is_empty() {
+        board.generate_tactical_moves(&mut visit);
+        board.generate_quiet_moves(&mut visit);
+    } else {
+        board.generate_evasions(&mut visit);
+    }
+    nodes
+}
+
+macro_rules! make_staged_perft_test {
+    ($($name:ident($board:expr, $depth:expr, $nodes:expr);)*) => {
+        $(#[test]
+        fn $name() {
+            let board = $board.parse::<Board>()
+                .or_else(|_| Board::from_fen($board, true))
+                .unwrap();
+            assert_eq!(staged_perft(&board, $depth), $nodes);
+        })*
+    };
+}
+
+make_staged_perft_test! {
+    staged_perft_startpos("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 4, 197281);
+    staged_perft_kiwipete("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 3, 97862);
+    staged_perft_position_3("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674624);
+    staged_perft_position_4("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 4, 422333);
+    staged_perft_position_5("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 3, 62379);
+    staged_perft_960_position_333("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9", 3, 14569);
+}
+
+#[test]
+fn staged_movegen_partitions_moves() {
+    fn visit(board: &Board, depth: u8) {
+        let mut all = HashSet::new();
+        board.generate_moves(|moves| {
+            all.extend(moves);
+            false
+        });
+        let mut tactical = HashSet::new();
+        board.generate_tactical_moves(|moves| {
+            tactical.extend(moves);
+            false
+        });
+        let mut quiet = HashSet::new();
+        board.generate_quiet_moves(|moves| {
+            quiet.extend(moves);
+            false
+        });
+        let mut evasions = HashSet::new();
+        board.generate_evasions(|moves| {
+            evasions.extend(moves);
+            false
+        });
+        let color = board.side_to_move();
+        let en_passant = board.en_passant()
+            .map(|file| Square::new(file, Rank::Sixth.relative_to(color)));
+        for &mv in &all {
+            let is_tactical = board.colors(!color).has(mv.to)
+                || mv.promotion.is_some()
+                || (board.piece_on(mv.from) == Some(Piece::Pawn) && Some(mv.to) == en_passant);
+            assert_eq!(tactical.contains(&mv), is_tactical, "{} {}", board, mv);
+            assert_eq!(quiet.contains(&mv), !is_tactical, "{} {}", board, mv);
+        }
+        assert_eq!(tactical.len() + quiet.len(), all.len(), "{}", board);
+        if board.checkers().is_empty() {
+            assert!(evasions.is_empty(), "{}", board);
+        } else {
+            assert_eq!(evasions, all, "{}", board);
+        }
+        if depth > 0 {
+            for mv in all {
+                let mut board = board.clone();
+                board.play_unchecked(mv);
+                visit(&board, depth - 1);
+            }
+        }
+    }
+    const FENS: &[&str] = &[
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"
+    ];
+    for fen in FENS {
+        visit(&fen.parse().unwrap(), 2);
+    }
+}
+
+#[test]
+fn subset_staged_movegen() {
+    for fen in include_str!("../test_data/valid.sfens").lines().take(1000) {
+        let board = Board::from_fen(fen, true).unwrap();
+        let subset_a = BitBoard(board.hash());
+        let subset_b = !subset_a;
+        let mut subset_moves = 0;
+        let mut total_moves = 0;
+        for &mask in &[subset_a, subset_b] {
+            board.generate_tactical_moves_for(mask, |moves| {
+                subset_moves += moves.len();
+                false
+            });
+            board.generate_quiet_moves_for(mask, |moves| {
+                subset_moves += moves.len();
+                false
+            });
+        }
+        board.generate_moves(|moves| {
+            total_moves += moves.len();
+            false
+        });
+        assert_eq!(subset_moves, total_moves, "{}", fen);
+    }
+}
+
 fn test_is_legal(board: Board) {
     let mut legals = HashSet::new();
     board.generate_moves(|mvs| {
@@ -282,3 +408,36 @@ fn legality_en_passant() {
             .unwrap(),
     );
 }
+
+#[test]
+fn legality_arbitrary_moves() {
+    for fen in include_str!("../test_data/valid.sfens").lines().take(1000) {
+        let board = Board::from_fen(fen, true).unwrap();
+        test_is_legal(board.clone());
+        //Few of these positions are in check, so evasions are tested in the positions after checks.
+        board.generate_moves(|moves| {
+            for mv in moves {
+                let mut child = board.clone();
+                child.play_unchecked(mv);
+                if !child.checkers().is_empty() {
+                    test_is_legal(child);
+                }
+            }
+            false
+        });
+    }
+}
+
+#[test]
+fn legality_castles_in_check() {
+    test_is_legal("8/8/pk2r3/1pR4Q/5P2/1N1n4/P3B1PP/4K2R w K - 1 40".parse().unwrap());
+    //Double check
+    test_is_legal("4r1k1/8/8/8/8/3n4/8/R3K2R w KQ - 0 1".parse().unwrap());
+    test_is_legal(
+        Board::from_fen(
+            "3rk1r1/p1nnppNp/bppp4/8/3PP3/1PP3P1/P5BP/1NBRK1R1 b GDgd - 0 12",
+            true,
+        )
+        .unwrap(),
+    );
+}
@@ -0,0 +1,248 @@
+use crate::*;
+
+use super::ZobristBoard;
+
+/// The current version of the packed board format. See [`Board::to_packed`].
+pub const PACKED_BOARD_VERSION: u8 = 1;
+
+/// The size in bytes of a packed board. See [`Board::to_packed`].
+pub const PACKED_BOARD_SIZE: usize = 32;
+
+/// An error while decoding a packed board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackedBoardError {
+    InvalidVersion,
+    InvalidBoard,
+    InvalidSideToMove,
+    InvalidCastlingRights,
+    InvalidEnPassant,
+    InvalidHalfMoveClock,
+    InvalidFullmoveNumber
+}
+
+//Nibble used for a missing castle right or en passant file.
+const NO_FILE: u8 = 0xF;
+
+const VERSION_OFFSET: usize = 0;
+const OCCUPIED_OFFSET: usize = 1;
+const PIECES_OFFSET: usize = 9;
+const SIDE_TO_MOVE_OFFSET: usize = 25;
+const CASTLE_RIGHTS_OFFSET: usize = 26;
+const EN_PASSANT_OFFSET: usize = 28;
+const HALFMOVE_CLOCK_OFFSET: usize = 29;
+const FULLMOVE_NUMBER_OFFSET: usize = 30;
+
+fn file_to_nibble(file: Option<File>) -> u8 {
+    file.map_or(NO_FILE, |file| file as u8)
+}
+
+fn nibble_to_file(nibble: u8) -> Result<Option<File>, ()> {
+    if nibble == NO_FILE {
+        return Ok(None);
+    }
+    File::try_index(nibble as usize).map(Some).ok_or(())
+}
+
+impl Board {
+    /// Encode the board into a compact, fixed-size binary format.
+    /// Unlike FEN, this is cheap to produce and decode, making it suitable for large datasets.
+    /// The layout (all integers little endian) is:
+    /// - Byte 0: the format version, [`PACKED_BOARD_VERSION`].
+    /// - Bytes 1-8: the occupied squares as a [`BitBoard`].
+    /// - Bytes 9-24: one nibble per occupied square in ascending square order, low nibble first.
+    ///   Each nibble stores the piece's index, plus 8 for black pieces. Unused nibbles are zero.
+    /// - Byte 25: the side to move's index.
+    /// - Bytes 26-27: the castle rights of white then black, with the short castle rook file
+    ///   in the low nibble and the long castle rook file in the high nibble.
+    /// - Byte 28: the en passant file.
+    /// - Byte 29: the halfmove clock.
+    /// - Bytes 30-31: the fullmove number.
+    ///
+    /// Missing files are stored as `0xF`.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_to_packed`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board = Board::default();
+    /// let packed = board.to_packed();
+    /// assert_eq!(packed.len(), PACKED_BOARD_SIZE);
+    /// assert_eq!(Board::from_packed(&packed).unwrap(), board);
+    /// ```
+    pub fn to_packed(&self) -> [u8; PACKED_BOARD_SIZE] {
+        self.try_to_packed().expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::to_packed`].
+    /// # Errors
+    /// See [`Board::to_packed`]'s panics.
+    pub fn try_to_packed(&self) -> Result<[u8; PACKED_BOARD_SIZE], BoardError> {
+        let occupied = self.occupied();
+        if occupied.popcnt() > 32 {
+            return Err(BoardError::InvalidBoard);
+        }
+        let mut packed = [0; PACKED_BOARD_SIZE];
+        packed[VERSION_OFFSET] = PACKED_BOARD_VERSION;
+        packed[OCCUPIED_OFFSET..PIECES_OFFSET].copy_from_slice(&occupied.0.to_le_bytes());
+        for (i, square) in occupied.into_iter().enumerate() {
+            let piece = self.piece_on(square).ok_or(BoardError::InvalidBoard)?;
+            let color = self.color_on(square).ok_or(BoardError::InvalidBoard)?;
+            let nibble = piece as u8 | (color as u8) << 3;
+            packed[PIECES_OFFSET + i / 2] |= nibble << (i % 2 * 4);
+        }
+        packed[SIDE_TO_MOVE_OFFSET] = self.side_to_move() as u8;
+        for &color in &Color::ALL {
+            let rights = self.castle_rights(color);
+            packed[CASTLE_RIGHTS_OFFSET + color as usize] =
+                file_to_nibble(rights.short) | file_to_nibble(rights.long) << 4;
+        }
+        packed[EN_PASSANT_OFFSET] = file_to_nibble(self.en_passant());
+        packed[HALFMOVE_CLOCK_OFFSET] = self.halfmove_clock;
+        packed[FULLMOVE_NUMBER_OFFSET..].copy_from_slice(&self.fullmove_number.to_le_bytes());
+        Ok(packed)
+    }
+
+    /// Decode a board encoded with [`Board::to_packed`].
+    /// Any input is accepted; the decoded board is checked for validity
+    /// and every encoding of a valid board is canonical.
+    /// # Errors
+    /// This errors if the version is not [`PACKED_BOARD_VERSION`],
+    /// if the data is malformed or if the decoded board is invalid.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let mut packed = Board::default().to_packed();
+    /// packed[0] = 0;
+    /// assert_eq!(Board::from_packed(&packed), Err(PackedBoardError::InvalidVersion));
+    /// ```
+    pub fn from_packed(packed: &[u8; PACKED_BOARD_SIZE]) -> Result<Self, PackedBoardError> {
+        use PackedBoardError::*;
+
+        if packed[VERSION_OFFSET] != PACKED_BOARD_VERSION {
+            return Err(InvalidVersion);
+        }
+        let mut board = Self {
+            inner: ZobristBoard::empty(),
+            pinned: BitBoard::EMPTY,
+            checkers: BitBoard::EMPTY,
+            halfmove_clock: 0,
+            fullmove_number: 0
+        };
+        Self::unpack_pieces(&mut board, packed)
+            .map_err(|_| InvalidBoard)?;
+        Self::unpack_side_to_move(&mut board, packed)
+            .map_err(|_| InvalidSideToMove)?;
+        if !board.board_is_valid() {
+            return Err(InvalidBoard);
+        }
+        Self::unpack_castle_rights(&mut board, packed)
+            .map_err(|_| InvalidCastlingRights)?;
+        if !board.castle_rights_are_valid() {
+            return Err(InvalidCastlingRights);
+        }
+        Self::unpack_en_passant(&mut board, packed)
+            .map_err(|_| InvalidEnPassant)?;
+        if !board.en_passant_is_valid() {
+            return Err(InvalidEnPassant);
+        }
+        board.halfmove_clock = packed[HALFMOVE_CLOCK_OFFSET];
+        if !board.halfmove_clock_is_valid() {
+            return Err(InvalidHalfMoveClock);
+        }
+        let mut fullmove_number = [0; 2];
+        fullmove_number.copy_from_slice(&packed[FULLMOVE_NUMBER_OFFSET..]);
+        board.fullmove_number = u16::from_le_bytes(fullmove_number);
+        if !board.fullmove_number_is_valid() {
+            return Err(InvalidFullmoveNumber);
+        }
+
+        let (checkers, pinned) = board.calculate_checkers_and_pins(board.side_to_move());
+        board.checkers = checkers;
+        board.pinned = pinned;
+        if !board.validity_check() {
+            return Err(InvalidBoard);
+        }
+
+        Ok(board)
+    }
+
+    fn unpack_pieces(board: &mut Board, packed: &[u8; PACKED_BOARD_SIZE]) -> Result<(), ()> {
+        let mut occupied = [0; 8];
+        occupied.copy_from_slice(&packed[OCCUPIED_OFFSET..PIECES_OFFSET]);
+        let occupied = BitBoard(u64::from_le_bytes(occupied));
+        let count = occupied.popcnt() as usize;
+        if count > 32 {
+            return Err(());
+        }
+        for i in 0..32 {
+            let nibble = packed[PIECES_OFFSET + i / 2] >> (i % 2 * 4) & 0xF;
+            if i >= count && nibble != 0 {
+                return Err(());
+            }
+        }
+        for (i, square) in occupied.into_iter().enumerate() {
+            let nibble = packed[PIECES_OFFSET + i / 2] >> (i % 2 * 4) & 0xF;
+            let piece = Piece::try_index((nibble & 0b111) as usize).ok_or(())?;
+            let color = Color::index((nibble >> 3) as usize);
+            board.inner.xor_square(piece, color, square);
+        }
+        Ok(())
+    }
+
+    fn unpack_side_to_move(board: &mut Board, packed: &[u8; PACKED_BOARD_SIZE]) -> Result<(), ()> {
+        let color = Color::try_index(packed[SIDE_TO_MOVE_OFFSET] as usize).ok_or(())?;
+        if color != board.side_to_move() {
+            board.inner.toggle_side_to_move();
+        }
+        Ok(())
+    }
+
+    fn unpack_castle_rights(board: &mut Board, packed: &[u8; PACKED_BOARD_SIZE]) -> Result<(), ()> {
+        for &color in &Color::ALL {
+            let rights = packed[CASTLE_RIGHTS_OFFSET + color as usize];
+            board.inner.set_castle_right(color, true, nibble_to_file(rights & 0xF)?);
+            board.inner.set_castle_right(color, false, nibble_to_file(rights >> 4)?);
+        }
+        Ok(())
+    }
+
+    fn unpack_en_passant(board: &mut Board, packed: &[u8; PACKED_BOARD_SIZE]) -> Result<(), ()> {
+        let en_passant = packed[EN_PASSANT_OFFSET];
+        if en_passant > 0xF {
+            return Err(());
+        }
+        board.inner.set_en_passant(nibble_to_file(en_passant)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_packed() {
+        for fen in include_str!("test_data/valid.sfens").lines() {
+            let board = Board::from_fen(fen, true).unwrap();
+            let packed = board.to_packed();
+            assert_eq!(Board::from_packed(&packed).unwrap(), board, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn corrupted_packed() {
+        for fen in include_str!("test_data/valid.sfens").lines().take(500) {
+            let packed = Board::from_fen(fen, true).unwrap().to_packed();
+            for bit in 0..PACKED_BOARD_SIZE * 8 {
+                let mut corrupted = packed;
+                corrupted[bit / 8] ^= 1 << (bit % 8);
+                //Either rejected or a different valid board with the same canonical encoding.
+                if let Ok(board) = Board::from_packed(&corrupted) {
+                    assert!(board.validity_check(), "{} {}", fen, bit);
+                    assert_eq!(board.to_packed(), corrupted, "{} {}", fen, bit);
+                }
+            }
+        }
+    }
+}
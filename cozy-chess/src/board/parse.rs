@@ -7,7 +7,7 @@ use crate::*;
 use super::ZobristBoard;
 
 /// An error while parsing the FEN.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FenParseError {
     InvalidBoard,
     InvalidSideToMove,
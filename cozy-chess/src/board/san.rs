@@ -0,0 +1,372 @@
+use core::convert::TryFrom;
+use core::fmt::{Display, Formatter};
+
+use crate::*;
+
+/// An error while parsing a move in Standard Algebraic Notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SanParseError {
+    /// The input is not well-formed SAN.
+    InvalidSan,
+    /// The input is well-formed, but no legal move matches it.
+    IllegalMove,
+    /// The input matches more than one legal move.
+    AmbiguousMove
+}
+
+/// A legal move in [Standard Algebraic Notation](https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29).
+/// Created with [`Board::san`]. Display it to get the SAN string.
+/// The alternate format mode appends `" e.p."` to en passant captures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SanMove {
+    piece: Piece,
+    from_file: Option<File>,
+    from_rank: Option<Rank>,
+    capture: bool,
+    en_passant: bool,
+    castle: Option<bool>,
+    to: Square,
+    promotion: Option<Piece>,
+    check: bool,
+    mate: bool
+}
+
+impl Display for SanMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some(short) = self.castle {
+            write!(f, "{}", if short { "O-O" } else { "O-O-O" })?;
+        } else {
+            if self.piece != Piece::Pawn {
+                let piece: char = self.piece.into();
+                write!(f, "{}", piece.to_ascii_uppercase())?;
+            }
+            if let Some(file) = self.from_file {
+                write!(f, "{}", file)?;
+            }
+            if let Some(rank) = self.from_rank {
+                write!(f, "{}", rank)?;
+            }
+            if self.capture {
+                write!(f, "x")?;
+            }
+            write!(f, "{}", self.to)?;
+            if let Some(promotion) = self.promotion {
+                let promotion: char = promotion.into();
+                write!(f, "={}", promotion.to_ascii_uppercase())?;
+            }
+            if self.en_passant && f.alternate() {
+                write!(f, " e.p.")?;
+            }
+        }
+        if self.mate {
+            write!(f, "#")?;
+        } else if self.check {
+            write!(f, "+")?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    /// Get the [`SanMove`] for a move, or [`None`] if the move is illegal.
+    /// Note that this only supports Chess960 style castling.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// See [`Board::try_san`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// let san = |mv: &str| format!("{}", board.san(mv.parse().unwrap()).unwrap());
+    /// assert_eq!(san("e5f7"), "Nxf7");
+    /// assert_eq!(san("d2c1"), "Bc1");
+    /// assert_eq!(san("e1h1"), "O-O");
+    /// assert_eq!(san("e1a1"), "O-O-O");
+    /// assert_eq!(san("d5e6"), "dxe6");
+    /// assert!(board.san("e1e8".parse().unwrap()).is_none());
+    /// ```
+    /// ## Disambiguation
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "7k/8/8/8/R3R3/8/8/R6K w - - 0 1".parse().unwrap();
+    /// let san = |mv: &str| format!("{}", board.san(mv.parse().unwrap()).unwrap());
+    /// assert_eq!(san("a4b4"), "Rab4");
+    /// assert_eq!(san("a4a3"), "R4a3");
+    /// assert_eq!(san("e4e8"), "Re8+");
+    /// ```
+    /// ## En passant
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3"
+    ///     .parse().unwrap();
+    /// let san = board.san("e5d6".parse().unwrap()).unwrap();
+    /// assert_eq!(format!("{}", san), "exd6");
+    /// assert_eq!(format!("{:#}", san), "exd6 e.p.");
+    /// ```
+    pub fn san(&self, mv: Move) -> Option<SanMove> {
+        self.try_san(mv).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::san`].
+    /// # Errors
+    /// See [`Board::san`]'s panics.
+    pub fn try_san(&self, mv: Move) -> Result<Option<SanMove>, BoardError> {
+        if !self.try_is_legal(mv)? {
+            return Ok(None);
+        }
+        let color = self.side_to_move();
+        let piece = self.piece_on(mv.from).ok_or(BoardError::InvalidBoard)?;
+        let our_pieces = self.colors(color);
+        let castle = if piece == Piece::King && our_pieces.has(mv.to) {
+            Some(mv.from.file() < mv.to.file())
+        } else {
+            None
+        };
+        let en_passant = piece == Piece::Pawn
+            && mv.from.file() != mv.to.file()
+            && !self.occupied().has(mv.to);
+        let capture = castle.is_none() && (en_passant || self.occupied().has(mv.to));
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        if piece == Piece::Pawn {
+            if capture {
+                from_file = Some(mv.from.file());
+            }
+        } else if piece != Piece::King {
+            let mut others = BitBoard::EMPTY;
+            let candidates = our_pieces & self.pieces(piece) & !mv.from.bitboard();
+            self.try_generate_moves_for(candidates, |moves| {
+                if moves.to.has(mv.to) {
+                    others |= moves.from.bitboard();
+                }
+                false
+            })?;
+            if !others.is_empty() {
+                if (others & mv.from.file().bitboard()).is_empty() {
+                    from_file = Some(mv.from.file());
+                } else if (others & mv.from.rank().bitboard()).is_empty() {
+                    from_rank = Some(mv.from.rank());
+                } else {
+                    from_file = Some(mv.from.file());
+                    from_rank = Some(mv.from.rank());
+                }
+            }
+        }
+
+        let check = self.try_gives_check(mv)?;
+        let mate = if check {
+            let mut child = self.clone();
+            child.try_play_unchecked(mv)?;
+            !child.try_generate_moves(|_| true)?
+        } else {
+            false
+        };
+
+        Ok(Some(SanMove {
+            piece,
+            from_file,
+            from_rank,
+            capture,
+            en_passant,
+            castle,
+            to: mv.to,
+            promotion: mv.promotion,
+            check,
+            mate
+        }))
+    }
+
+    /// Parse a move in Standard Algebraic Notation.
+    /// Castling is returned as a Chess960 style king captures rook move.
+    /// Both `O` and `0` are accepted for castling. Trailing check, checkmate, `e.p.`
+    /// and `!`/`?` annotations are accepted but not validated. Capture markers are
+    /// also not validated.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// assert_eq!(board.parse_san("Nxf7"), Ok("e5f7".parse().unwrap()));
+    /// assert_eq!(board.parse_san("O-O-O"), Ok("e1a1".parse().unwrap()));
+    /// assert_eq!(board.parse_san("g4"), Ok("g2g4".parse().unwrap()));
+    /// assert_eq!(board.parse_san("Ke3"), Err(SanParseError::IllegalMove));
+    /// assert_eq!(board.parse_san("Zz9"), Err(SanParseError::InvalidSan));
+    ///
+    /// let board: Board = "7k/8/8/8/R3R3/8/8/R6K w - - 0 1".parse().unwrap();
+    /// assert_eq!(board.parse_san("Ra3"), Err(SanParseError::AmbiguousMove));
+    /// assert_eq!(board.parse_san("R1a3"), Ok("a1a3".parse().unwrap()));
+    /// ```
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanParseError> {
+        use SanParseError::*;
+
+        let is_annotation = |c| matches!(c, '+' | '#' | '!' | '?');
+        let san = san.trim().trim_end_matches(is_annotation);
+        let san = san.strip_suffix("e.p.").unwrap_or(san).trim_end();
+        let san = san.trim_end_matches(is_annotation);
+        if !san.is_ascii() {
+            return Err(InvalidSan);
+        }
+
+        let color = self.side_to_move();
+        let our_king = self.king(color);
+        let castle = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None
+        };
+        if let Some(short) = castle {
+            let rights = self.castle_rights(color);
+            let rook = if short { rights.short } else { rights.long }.ok_or(IllegalMove)?;
+            let mv = Move {
+                from: our_king,
+                to: Square::new(rook, Rank::First.relative_to(color)),
+                promotion: None
+            };
+            return if self.is_legal(mv) { Ok(mv) } else { Err(IllegalMove) };
+        }
+
+        let (piece, san) = match san.as_bytes().first() {
+            Some(b'N') => (Piece::Knight, &san[1..]),
+            Some(b'B') => (Piece::Bishop, &san[1..]),
+            Some(b'R') => (Piece::Rook, &san[1..]),
+            Some(b'Q') => (Piece::Queen, &san[1..]),
+            Some(b'K') => (Piece::King, &san[1..]),
+            _ => (Piece::Pawn, san)
+        };
+        let (san, promotion) = match san.as_bytes().last() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let promotion = Piece::try_from(c.to_ascii_lowercase() as char)
+                    .map_err(|_| InvalidSan)?;
+                if piece != Piece::Pawn || matches!(promotion, Piece::Pawn | Piece::King) {
+                    return Err(InvalidSan);
+                }
+                let san = &san[..san.len() - 1];
+                (san.strip_suffix('=').unwrap_or(san), Some(promotion))
+            }
+            _ => (san, None)
+        };
+        let split = san.len().checked_sub(2).ok_or(InvalidSan)?;
+        let to = san[split..].parse::<Square>().map_err(|_| InvalidSan)?;
+        let san = &san[..split];
+        let san = san.strip_suffix('x').unwrap_or(san);
+
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in san.chars() {
+            if let (None, None, Ok(file)) = (from_file, from_rank, File::try_from(c)) {
+                from_file = Some(file);
+            } else if let (None, Ok(rank)) = (from_rank, Rank::try_from(c)) {
+                from_rank = Some(rank);
+            } else {
+                return Err(InvalidSan);
+            }
+        }
+
+        let mut mask = self.colors(color) & self.pieces(piece);
+        if let Some(file) = from_file {
+            mask &= file.bitboard();
+        }
+        if let Some(rank) = from_rank {
+            mask &= rank.bitboard();
+        }
+        let mut found = None;
+        let mut ambiguous = false;
+        self.generate_moves_for(mask, |moves| {
+            for mv in moves {
+                //Castling is only written with O-O and O-O-O.
+                let is_castle = self.colors(color).has(mv.to);
+                if mv.to == to && mv.promotion == promotion && !is_castle {
+                    if found.is_some() {
+                        ambiguous = true;
+                        return true;
+                    }
+                    found = Some(mv);
+                }
+            }
+            false
+        });
+        if ambiguous {
+            return Err(AmbiguousMove);
+        }
+        found.ok_or(IllegalMove)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(board: &Board, depth: u8) {
+        board.generate_moves(|moves| {
+            for mv in moves {
+                let san = board.san(mv).unwrap();
+                let formatted = format!("{}", san);
+                assert_eq!(board.parse_san(&formatted), Ok(mv), "{} {}", board, formatted);
+                let formatted = format!("{:#}", san);
+                assert_eq!(board.parse_san(&formatted), Ok(mv), "{} {}", board, formatted);
+                if depth > 0 {
+                    let mut board = board.clone();
+                    board.play_unchecked(mv);
+                    round_trip(&board, depth - 1);
+                }
+            }
+            false
+        });
+    }
+
+    #[test]
+    fn san_round_trip_kiwipete() {
+        let board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+            .parse().unwrap();
+        round_trip(&board, 2);
+    }
+
+    #[test]
+    fn san_round_trip_960() {
+        let board = Board::from_fen(
+            "rq1kr3/p1ppbp1p/bpn3pB/3Np3/3P4/1P1Q1Nn1/P1P1BPPP/R2KR3 w AEae - 3 15",
+            true
+        ).unwrap();
+        round_trip(&board, 2);
+    }
+
+    #[test]
+    fn san_disambiguation() {
+        let board: Board = "7k/8/8/8/R3R3/8/8/R6K w - - 0 1".parse().unwrap();
+        let san = |mv: &str| format!("{}", board.san(mv.parse().unwrap()).unwrap());
+        assert_eq!(san("a4b4"), "Rab4");
+        assert_eq!(san("a4a3"), "R4a3");
+        assert_eq!(san("a1a3"), "R1a3");
+        assert_eq!(san("a4c4"), "Rac4");
+        assert_eq!(san("e4e8"), "Re8+");
+        assert_eq!(board.parse_san("Ra3"), Err(SanParseError::AmbiguousMove));
+        assert_eq!(board.parse_san("R4a3"), Ok("a4a3".parse().unwrap()));
+        assert_eq!(board.parse_san("Ra4a3"), Ok("a4a3".parse().unwrap()));
+
+        let board: Board = "1k6/8/8/8/Q1Q5/8/Q7/7K w - - 0 1".parse().unwrap();
+        let san = |mv: &str| format!("{}", board.san(mv.parse().unwrap()).unwrap());
+        assert_eq!(san("a4b3"), "Qa4b3#");
+        assert_eq!(board.parse_san("Qa4b3"), Ok("a4b3".parse().unwrap()));
+    }
+
+    #[test]
+    fn san_promotions_and_checks() {
+        let board: Board = "3r2k1/4P3/6K1/8/8/8/8/8 w - - 0 1".parse().unwrap();
+        let san = |mv: &str| format!("{}", board.san(mv.parse().unwrap()).unwrap());
+        assert_eq!(san("e7e8q"), "e8=Q+");
+        assert_eq!(san("e7d8q"), "exd8=Q#");
+        assert_eq!(san("e7d8n"), "exd8=N");
+        assert_eq!(board.parse_san("exd8=Q+"), Ok("e7d8q".parse().unwrap()));
+        assert_eq!(board.parse_san("exd8Q"), Ok("e7d8q".parse().unwrap()));
+        assert_eq!(board.parse_san("e8"), Err(SanParseError::IllegalMove));
+        assert_eq!(board.parse_san("e8=K"), Err(SanParseError::InvalidSan));
+
+        let board: Board = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".parse().unwrap();
+        assert_eq!(format!("{}", board.san("a1a8".parse().unwrap()).unwrap()), "Ra8#");
+        assert_eq!(board.parse_san("Ra8#"), Ok("a1a8".parse().unwrap()));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::*;
+
+/// Default piece values for [`Board::see`], indexed by [`Piece`].
+pub const SEE_VALUES: [i16; Piece::NUM] = [100, 300, 300, 500, 900, 0];
+
+impl Board {
+    /// Compute the [static exchange evaluation](https://www.chessprogramming.org/Static_Exchange_Evaluation)
+    /// of a move, which is the material outcome of the capture sequence it starts on its destination square,
+    /// assuming both sides only continue capturing while it benefits them.
+    /// Pieces are valued with `values`, indexed by [`Piece`]. See [`SEE_VALUES`] for some defaults.
+    /// X-ray attackers, promotions and en passant are handled; pins are ignored.
+    /// The move is assumed to be legal. The result for illegal moves is unspecified.
+    /// Castling always evaluates to 0.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1".parse().unwrap();
+    /// assert_eq!(board.see("d3e5".parse().unwrap(), &SEE_VALUES), -200);
+    /// assert_eq!(board.see("e2e5".parse().unwrap(), &SEE_VALUES), -400);
+    /// ```
+    pub fn see(&self, mv: Move, values: &[i16; Piece::NUM]) -> i16 {
+        let color = self.side_to_move();
+        let moved = match self.piece_on(mv.from) {
+            Some(piece) => piece,
+            None => return 0
+        };
+        if self.colors(color).has(mv.to) {
+            //Castling
+            return 0;
+        }
+        let value = |piece: Piece| values[piece as usize];
+        let promotion_gain = |piece: Piece| value(piece) - value(Piece::Pawn);
+
+        let mut occupied = self.occupied() ^ mv.from.bitboard();
+        let en_passant = self.en_passant()
+            .map(|file| Square::new(file, Rank::Sixth.relative_to(color)));
+        let mut gains = [0; 32];
+        gains[0] = if moved == Piece::Pawn && Some(mv.to) == en_passant {
+            let victim_square = Square::new(mv.to.file(), Rank::Fifth.relative_to(color));
+            occupied ^= victim_square.bitboard();
+            value(Piece::Pawn)
+        } else {
+            self.piece_on(mv.to).map_or(0, value)
+        };
+        let mut on_square = moved;
+        if let Some(promotion) = mv.promotion {
+            gains[0] += promotion_gain(promotion);
+            on_square = promotion;
+        }
+
+        let mut depth = 0;
+        let mut color = !color;
+        loop {
+            let attackers = self.attackers(mv.to, color, occupied);
+            let attacker = Piece::ALL.iter().find_map(|&piece| {
+                let square = (attackers & self.pieces(piece)).next_square()?;
+                Some((piece, square))
+            });
+            let (attacker, attacker_square) = match attacker {
+                Some(attacker) => attacker,
+                None => break
+            };
+            occupied ^= attacker_square.bitboard();
+            if attacker == Piece::King && !self.attackers(mv.to, !color, occupied).is_empty() {
+                //The king can't capture into check.
+                break;
+            }
+            depth += 1;
+            gains[depth] = value(on_square) - gains[depth - 1];
+            on_square = attacker;
+            if attacker == Piece::Pawn && mv.to.rank() == Rank::Eighth.relative_to(color) {
+                gains[depth] += promotion_gain(Piece::Queen);
+                on_square = Piece::Queen;
+            }
+            color = !color;
+        }
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// Check if the [static exchange evaluation](Board::see) of a move is at least `threshold`.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board: Board = "1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1".parse().unwrap();
+    /// let mv = "e1e5".parse().unwrap();
+    /// assert!(board.see_ge(mv, 100, &SEE_VALUES));
+    /// assert!(!board.see_ge(mv, 101, &SEE_VALUES));
+    /// ```
+    pub fn see_ge(&self, mv: Move, threshold: i16, values: &[i16; Piece::NUM]) -> bool {
+        self.see(mv, values) >= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn see_values() {
+        const TESTS: &[(&str, &str, i16)] = &[
+            ("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5", 100),
+            ("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5", -200),
+            ("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", 100),
+            ("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1", "d2d5", -400),
+            ("3rk3/8/8/8/8/8/8/3RK3 w - - 0 1", "d1d6", -500),
+            ("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100),
+            ("4k3/2p5/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 0),
+            ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", 800),
+            ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n", 200),
+            ("1rk5/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", 400),
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", 1300),
+            ("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1", "b2a1q", 1300),
+            ("4k3/8/8/8/8/1N6/1p6/R3K3 b - - 0 1", "b2a1q", 400),
+            ("4k3/8/8/3r4/4K3/8/8/8 w - - 0 1", "e4d5", 500),
+            ("8/4k3/4p3/8/8/8/4R3/4RK2 w - - 0 1", "e2e6", 100),
+            ("8/4k3/4p3/8/8/8/4R3/5K2 w - - 0 1", "e2e6", -400),
+            ("4k3/8/3p4/4n3/8/8/8/4R1K1 w - - 0 1", "e1e5", -200),
+            ("4k3/8/8/5n2/3p4/8/8/3Q2K1 w - - 0 1", "d1d4", -800),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1", 0)
+        ];
+        for &(fen, mv, expected) in TESTS {
+            let board: Board = fen.parse().unwrap();
+            let mv = mv.parse().unwrap();
+            assert!(board.is_legal(mv), "{} {}", fen, mv);
+            assert_eq!(board.see(mv, &SEE_VALUES), expected, "{} {}", fen, mv);
+        }
+    }
+}
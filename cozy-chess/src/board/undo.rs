@@ -0,0 +1,190 @@
+use crate::*;
+
+/// A record of the state lost when playing a move,
+/// used to take the move back with [`Board::unplay`].
+/// See [`Board::play_with_undo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    mv: Move,
+    moved: Piece,
+    victim: Option<Piece>,
+    is_castle: bool,
+    is_en_passant: bool,
+    castle_rights: [CastleRights; Color::NUM],
+    en_passant: Option<File>,
+    halfmove_clock: u8,
+    pinned: BitBoard,
+    checkers: BitBoard
+}
+
+impl Undo {
+    /// The move that was played.
+    pub fn mv(&self) -> Move {
+        self.mv
+    }
+
+    /// The piece captured by the move, if any. This is [`None`] for castling.
+    pub fn victim(&self) -> Option<Piece> {
+        self.victim
+    }
+}
+
+impl Board {
+    /// Play a move without checking its legality, returning an [`Undo`]
+    /// record that can be passed to [`Board::unplay`] to take it back.
+    /// This is an alternative to cloning the board before playing a move.
+    /// Note that this only supports Chess960 style castling.
+    /// # Panics
+    /// This may panic if the board or move is invalid. However, this is not guaranteed.
+    /// See [`Board::try_play_with_undo`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let mut board = Board::default();
+    /// let original = board.clone();
+    /// let e4 = board.play_with_undo("e2e4".parse().unwrap());
+    /// let e5 = board.play_with_undo("e7e5".parse().unwrap());
+    /// const EXPECTED: &str = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2";
+    /// assert_eq!(format!("{}", board), EXPECTED);
+    /// board.unplay(e5);
+    /// board.unplay(e4);
+    /// assert_eq!(board, original);
+    /// ```
+    pub fn play_with_undo(&mut self, mv: Move) -> Undo {
+        self.try_play_with_undo(mv).expect("Invalid board!")
+    }
+
+    /// Non-panicking version of [`Board::play_with_undo`].
+    /// # Errors
+    /// See [`Board::play_with_undo`]'s panics.
+    pub fn try_play_with_undo(&mut self, mv: Move) -> Result<Undo, BoardError> {
+        let color = self.side_to_move();
+        let moved = self.piece_on(mv.from).ok_or(BoardError::InvalidBoard)?;
+        let is_castle = (self.colors(color) & (mv.from.bitboard() ^ mv.to.bitboard())).popcnt() == 2;
+        let en_passant = self.en_passant()
+            .map(|file| Square::new(file, Rank::Sixth.relative_to(color)));
+        let is_en_passant = moved == Piece::Pawn && Some(mv.to) == en_passant;
+        let victim = if is_castle {
+            None
+        } else if is_en_passant {
+            Some(Piece::Pawn)
+        } else {
+            self.piece_on(mv.to)
+        };
+        let undo = Undo {
+            mv,
+            moved,
+            victim,
+            is_castle,
+            is_en_passant,
+            castle_rights: [
+                *self.castle_rights(Color::White),
+                *self.castle_rights(Color::Black)
+            ],
+            en_passant: self.en_passant(),
+            halfmove_clock: self.halfmove_clock,
+            pinned: self.pinned,
+            checkers: self.checkers
+        };
+        self.try_play_unchecked(mv)?;
+        Ok(undo)
+    }
+
+    /// Take back the last move played with [`Board::play_with_undo`],
+    /// restoring the board exactly as it was before the move.
+    /// Passing any other [`Undo`] record will leave the board in an invalid state.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let mut board: Board = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+    ///     .parse().unwrap();
+    /// let original = board.clone();
+    /// let undo = board.play_with_undo("e1h1".parse().unwrap());
+    /// assert_eq!(undo.victim(), None);
+    /// board.unplay(undo);
+    /// assert_eq!(board, original);
+    /// ```
+    pub fn unplay(&mut self, undo: Undo) {
+        let mv = undo.mv;
+        self.inner.toggle_side_to_move();
+        let color = self.side_to_move();
+        if color == Color::Black {
+            self.fullmove_number -= 1;
+        }
+
+        if undo.is_castle {
+            let back_rank = Rank::First.relative_to(color);
+            let (king, rook) = if mv.from.file() < mv.to.file() {
+                (File::G, File::F)
+            } else {
+                (File::C, File::D)
+            };
+            self.inner.xor_square(Piece::King, color, Square::new(king, back_rank));
+            self.inner.xor_square(Piece::Rook, color, Square::new(rook, back_rank));
+            self.inner.xor_square(Piece::King, color, mv.from);
+            self.inner.xor_square(Piece::Rook, color, mv.to);
+        } else {
+            let dropped = mv.promotion.unwrap_or(undo.moved);
+            self.inner.xor_square(dropped, color, mv.to);
+            self.inner.xor_square(undo.moved, color, mv.from);
+            if let Some(victim) = undo.victim {
+                let victim_square = if undo.is_en_passant {
+                    Square::new(mv.to.file(), Rank::Fifth.relative_to(color))
+                } else {
+                    mv.to
+                };
+                self.inner.xor_square(victim, !color, victim_square);
+            }
+        }
+
+        for (&color, rights) in Color::ALL.iter().zip(undo.castle_rights) {
+            self.inner.set_castle_right(color, true, rights.short);
+            self.inner.set_castle_right(color, false, rights.long);
+        }
+        self.inner.set_en_passant(undo.en_passant);
+        self.halfmove_clock = undo.halfmove_clock;
+        self.pinned = undo.pinned;
+        self.checkers = undo.checkers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_undo(board: &mut Board, depth: u8) {
+        if depth == 0 {
+            return;
+        }
+        let original = board.clone();
+        let mut moves = Vec::new();
+        board.generate_moves(|piece_moves| {
+            moves.extend(piece_moves);
+            false
+        });
+        for mv in moves {
+            let undo = board.play_with_undo(mv);
+            let mut played = original.clone();
+            played.play_unchecked(mv);
+            assert_eq!(*board, played, "{} {}", original, mv);
+            test_undo(board, depth - 1);
+            board.unplay(undo);
+            assert_eq!(*board, original, "{} {}", original, mv);
+        }
+    }
+
+    #[test]
+    fn unplay_restores_board() {
+        const FENS: &[&str] = &[
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w HAha - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w ha - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w HA - 1 8",
+            "rq1kr3/p1ppbp1p/bpn3pB/3Np3/3P4/1P1Q1Nn1/P1P1BPPP/R2KR3 w AEae - 3 15",
+            "1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1"
+        ];
+        for fen in FENS {
+            test_undo(&mut Board::from_fen(fen, true).unwrap(), 3);
+        }
+    }
+}
@@ -13,8 +13,9 @@ struct ZobristConstants {
     black_to_move: u64
 }
 
+//A static, so that the tables are only stored once.
 #[allow(clippy::eval_order_dependence)]
-const ZOBRIST: ZobristConstants = {
+static ZOBRIST: ZobristConstants = {
     //Simple Pcg64Mcg impl
     let mut state = 0x7369787465656E2062797465206E756Du128 | 1;
     macro_rules! rand {
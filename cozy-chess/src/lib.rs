@@ -27,6 +27,8 @@ pub use chess_move::*;
 
 mod board;
 mod moves;
+mod polyglot;
 
 pub use board::*;
 pub use moves::*;
+pub use polyglot::*;
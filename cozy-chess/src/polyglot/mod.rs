@@ -0,0 +1,348 @@
+use crate::*;
+
+mod random;
+
+use random::*;
+
+const CASTLE_RIGHTS_OFFSET: usize = 768;
+const EN_PASSANT_OFFSET: usize = 772;
+const WHITE_TO_MOVE_OFFSET: usize = 780;
+
+/// The size in bytes of a single entry in a Polyglot book.
+pub const POLYGLOT_ENTRY_SIZE: usize = 16;
+
+/// An entry in a Polyglot opening book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PolyglotEntry {
+    /// The Polyglot key of the position.
+    pub key: u64,
+    /// The move in Polyglot's 16-bit move format. See [`Board::polyglot_move`].
+    pub mv: u16,
+    /// The weight of the move, relative to the other moves in the position.
+    pub weight: u16,
+    /// The learning data of the move. This is unused by most tools.
+    pub learn: u32
+}
+
+impl PolyglotEntry {
+    /// Decode an entry from its big endian binary representation.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let bytes = [
+    ///     0x46, 0x3B, 0x96, 0x18, 0x16, 0x91, 0xFC, 0x9C,
+    ///     0x03, 0x1C, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x00
+    /// ];
+    /// let entry = PolyglotEntry::from_bytes(&bytes);
+    /// assert_eq!(entry.key, 0x463B96181691FC9C);
+    /// assert_eq!(entry.mv, 0x031C);
+    /// assert_eq!(entry.weight, 10);
+    /// ```
+    pub fn from_bytes(bytes: &[u8; POLYGLOT_ENTRY_SIZE]) -> Self {
+        let mut key = [0; 8];
+        key.copy_from_slice(&bytes[0..8]);
+        let mut learn = [0; 4];
+        learn.copy_from_slice(&bytes[12..16]);
+        Self {
+            key: u64::from_be_bytes(key),
+            mv: u16::from_be_bytes([bytes[8], bytes[9]]),
+            weight: u16::from_be_bytes([bytes[10], bytes[11]]),
+            learn: u32::from_be_bytes(learn)
+        }
+    }
+}
+
+/// An error while loading a Polyglot book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolyglotBookError {
+    InvalidLength,
+    UnsortedEntries
+}
+
+/// A Polyglot opening book, read directly from the bytes of a `.bin` file.
+/// The book borrows its data, so it can be used with [`include_bytes!`] in `no_std` environments.
+#[derive(Debug, Clone, Copy)]
+pub struct PolyglotBook<'b> {
+    data: &'b [u8]
+}
+
+impl<'b> PolyglotBook<'b> {
+    /// Load a book from the contents of a `.bin` file.
+    /// # Errors
+    /// This errors if the data is not a whole number of entries
+    /// or if the entries are not sorted by key, as required by the format.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// assert!(PolyglotBook::new(&[]).unwrap().is_empty());
+    /// assert_eq!(PolyglotBook::new(&[0; 15]).unwrap_err(), PolyglotBookError::InvalidLength);
+    /// ```
+    pub fn new(data: &'b [u8]) -> Result<Self, PolyglotBookError> {
+        if !data.len().is_multiple_of(POLYGLOT_ENTRY_SIZE) {
+            return Err(PolyglotBookError::InvalidLength);
+        }
+        let book = Self { data };
+        for i in 1..book.len() {
+            if book.entry(i - 1).key > book.entry(i).key {
+                return Err(PolyglotBookError::UnsortedEntries);
+            }
+        }
+        Ok(book)
+    }
+
+    /// Get the number of entries in the book.
+    pub fn len(&self) -> usize {
+        self.data.len() / POLYGLOT_ENTRY_SIZE
+    }
+
+    /// Check if the book has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn entry(&self, index: usize) -> PolyglotEntry {
+        let start = index * POLYGLOT_ENTRY_SIZE;
+        let mut bytes = [0; POLYGLOT_ENTRY_SIZE];
+        bytes.copy_from_slice(&self.data[start..start + POLYGLOT_ENTRY_SIZE]);
+        PolyglotEntry::from_bytes(&bytes)
+    }
+
+    /// Get all entries with a key, in the order they appear in the book.
+    pub fn entries(&self, key: u64) -> impl Iterator<Item = PolyglotEntry> + 'b {
+        let book = *self;
+        //Binary search for the first entry with the key.
+        let mut low = 0;
+        let mut high = book.len();
+        while low < high {
+            let mid = (low + high) / 2;
+            if book.entry(mid).key < key {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low..book.len())
+            .map(move |index| book.entry(index))
+            .take_while(move |entry| entry.key == key)
+    }
+
+    /// Pick a legal book move for a board, with probability proportional to its weight.
+    /// `random` is a uniformly random number.
+    /// Entries with moves that aren't legal on `board` are ignored,
+    /// so a key collision never results in an illegal move.
+    /// Returns [`None`] if there are no legal entries with a nonzero weight.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    pub fn weighted_move(&self, board: &Board, random: u64) -> Option<Move> {
+        let key = board.polyglot_key();
+        let legal_moves = || self.entries(key)
+            .filter(|entry| entry.weight > 0)
+            .filter_map(|entry| Some((board.polyglot_move(entry.mv)?, entry.weight as u64)));
+        let total: u64 = legal_moves().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut choice = random % total;
+        for (mv, weight) in legal_moves() {
+            if choice < weight {
+                return Some(mv);
+            }
+            choice -= weight;
+        }
+        unreachable!()
+    }
+}
+
+impl Board {
+    /// Get the key of this board in a Polyglot book.
+    /// Unlike [`Board::hash`], this is the standard hash used by other tools.
+    /// The en passant file is only included if a pawn can capture en passant,
+    /// and castling rights only record whether each side can castle, as in standard chess.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let mut board = Board::default();
+    /// assert_eq!(board.polyglot_key(), 0x463B96181691FC9C);
+    /// board.play_unchecked("e2e4".parse().unwrap());
+    /// assert_eq!(board.polyglot_key(), 0x823C9B50FD114196);
+    /// ```
+    pub fn polyglot_key(&self) -> u64 {
+        let mut key = 0;
+        for &color in &Color::ALL {
+            for &piece in &Piece::ALL {
+                //Black pieces come before white pieces of the same kind.
+                let kind = piece as usize * 2 + (color == Color::White) as usize;
+                for square in self.colors(color) & self.pieces(piece) {
+                    key ^= POLYGLOT_RANDOM[kind * Square::NUM + square as usize];
+                }
+            }
+            let rights = self.castle_rights(color);
+            let offset = CASTLE_RIGHTS_OFFSET + color as usize * 2;
+            if rights.short.is_some() {
+                key ^= POLYGLOT_RANDOM[offset];
+            }
+            if rights.long.is_some() {
+                key ^= POLYGLOT_RANDOM[offset + 1];
+            }
+        }
+        let color = self.side_to_move();
+        if let Some(file) = self.en_passant() {
+            let target = Square::new(file, Rank::Sixth.relative_to(color));
+            let capturers = self.colors(color) & self.pieces(Piece::Pawn) & get_pawn_attacks(target, !color);
+            if !capturers.is_empty() {
+                key ^= POLYGLOT_RANDOM[EN_PASSANT_OFFSET + file as usize];
+            }
+        }
+        if color == Color::White {
+            key ^= POLYGLOT_RANDOM[WHITE_TO_MOVE_OFFSET];
+        }
+        key
+    }
+
+    /// Decode a move in Polyglot's 16-bit move format.
+    /// Returns [`None`] if the move is malformed or illegal.
+    /// Castling is encoded as the king capturing its rook in both formats.
+    /// # Panics
+    /// This may panic if the board is invalid. However, this is not guaranteed.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// let board = Board::default();
+    /// assert_eq!(board.polyglot_move(0x031C), Some("e2e4".parse().unwrap()));
+    /// assert_eq!(board.polyglot_move(0x0124), None);
+    /// ```
+    pub fn polyglot_move(&self, mv: u16) -> Option<Move> {
+        let square = |bits: u16| Square::new(
+            File::index((bits & 0b111) as usize),
+            Rank::index((bits >> 3 & 0b111) as usize)
+        );
+        let promotion = match mv >> 12 {
+            0 => None,
+            1 => Some(Piece::Knight),
+            2 => Some(Piece::Bishop),
+            3 => Some(Piece::Rook),
+            4 => Some(Piece::Queen),
+            _ => return None
+        };
+        let mv = Move {
+            from: square(mv >> 6),
+            to: square(mv),
+            promotion
+        };
+        if self.is_legal(mv) {
+            Some(mv)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_bytes(key: u64, mv: &str, weight: u16) -> [u8; POLYGLOT_ENTRY_SIZE] {
+        let mv: Move = mv.parse().unwrap();
+        let promotion = match mv.promotion {
+            None => 0,
+            Some(Piece::Knight) => 1,
+            Some(Piece::Bishop) => 2,
+            Some(Piece::Rook) => 3,
+            Some(Piece::Queen) => 4,
+            _ => unreachable!()
+        };
+        let encoded = mv.to as u16 | (mv.from as u16) << 6 | promotion << 12;
+        let mut bytes = [0; POLYGLOT_ENTRY_SIZE];
+        bytes[0..8].copy_from_slice(&key.to_be_bytes());
+        bytes[8..10].copy_from_slice(&encoded.to_be_bytes());
+        bytes[10..12].copy_from_slice(&weight.to_be_bytes());
+        bytes
+    }
+
+    fn book_data(entries: &[(u64, &str, u16)]) -> Vec<u8> {
+        entries.iter()
+            .flat_map(|&(key, mv, weight)| entry_bytes(key, mv, weight))
+            .collect()
+    }
+
+    #[test]
+    fn polyglot_moves() {
+        const TESTS: &[(&str, &str)] = &[
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1f3"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8a8"),
+            ("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n"),
+            ("4k3/8/8/8/8/8/1p6/R3K3 b - - 0 1", "b2a1q")
+        ];
+        for &(fen, mv) in TESTS {
+            let board: Board = fen.parse().unwrap();
+            let mv: Move = mv.parse().unwrap();
+            let entry = PolyglotEntry::from_bytes(&entry_bytes(0, &mv.to_string(), 1));
+            assert_eq!(board.polyglot_move(entry.mv), Some(mv), "{} {}", fen, mv);
+        }
+        let board = Board::default();
+        for mv in 0..=u16::MAX {
+            if let Some(mv) = board.polyglot_move(mv) {
+                assert!(board.is_legal(mv));
+            }
+        }
+    }
+
+    #[test]
+    fn polyglot_keys() {
+        //The examples from the Polyglot book format specification.
+        const TESTS: &[(&str, u64)] = &[
+            ("", 0x463B96181691FC9C),
+            ("e2e4", 0x823C9B50FD114196),
+            ("e2e4 d7d5", 0x0756B94461C50FB0),
+            ("e2e4 d7d5 e4e5", 0x662FAFB965DB29D4),
+            ("e2e4 d7d5 e4e5 f7f5", 0x22A48B5A8E47FF78),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2", 0x652A607CA3F242C1),
+            ("e2e4 d7d5 e4e5 f7f5 e1e2 e8f7", 0x00FDD303C946BDD9),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4", 0x3C8123EA7B067637),
+            ("a2a4 b7b5 h2h4 b5b4 c2c4 b4c3 a1a3", 0x5C3F9B829B279560)
+        ];
+        for &(moves, key) in TESTS {
+            let mut board = Board::default();
+            for mv in moves.split_whitespace() {
+                board.play(mv.parse().unwrap());
+            }
+            assert_eq!(board.polyglot_key(), key, "{}", moves);
+        }
+    }
+
+    #[test]
+    fn book_lookup() {
+        let board = Board::default();
+        let key = board.polyglot_key();
+        let data = book_data(&[
+            (key - 1, "e2e4", 1),
+            (key, "e2e4", 3),
+            (key, "d2d4", 1),
+            (key, "e1e2", 0),
+            (key, "e2e5", 100),
+            (key + 1, "g1f3", 1)
+        ]);
+        let book = PolyglotBook::new(&data).unwrap();
+        assert_eq!(book.len(), 6);
+        assert_eq!(book.entries(key).count(), 4);
+        assert_eq!(book.entries(key - 2).count(), 0);
+        assert_eq!(book.entries(key + 2).count(), 0);
+
+        let mut counts = [0; 2];
+        for random in 0..400 {
+            match book.weighted_move(&board, random).unwrap().to_string().as_str() {
+                "e2e4" => counts[0] += 1,
+                "d2d4" => counts[1] += 1,
+                mv => panic!("Unexpected move {}", mv)
+            }
+        }
+        assert_eq!(counts, [300, 100]);
+        let mut board = board;
+        board.play("g1f3".parse().unwrap());
+        assert_eq!(book.weighted_move(&board, 0), None);
+
+        let unsorted = book_data(&[(5, "e2e4", 1), (1, "e2e4", 1)]);
+        assert_eq!(PolyglotBook::new(&unsorted).unwrap_err(), PolyglotBookError::UnsortedEntries);
+    }
+}
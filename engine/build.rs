@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::fs::File;
+
+use cozy_chess::*;
+
+#[path = "src/book/key.rs"]
+mod key;
+#[path = "src/book/text.rs"]
+mod text;
+
+use key::book_key;
+use text::*;
+
+fn main() {
+    let book_data = parse_book(include_str!("book.txt"))
+        .unwrap_or_else(|error| panic!("book.txt:{}", error));
+
+    let mut book: PathBuf = std::env::var("OUT_DIR").unwrap().into();
+    book.push("book.rs");
+    let mut book = BufWriter::new(File::create(book).unwrap());
+
+    writeln!(&mut book, "fn book_entry(board: &Board) -> Option<BookEntry> {{").unwrap();
+    writeln!(&mut book, "    Some(match board.hash() {{").unwrap();
+    for (hash, entry) in &book_data {
+        write!(&mut book, "        {} => ({}, &[", hash, book_key(&entry.board)).unwrap();
+        for &(mv, weight) in &entry.moves {
+            let weight = u16::try_from(weight)
+                .unwrap_or_else(|_| panic!("book.txt:{}: weight of {} in {} is too large", entry.line, mv, entry.board));
+            write!(&mut book, "({},{}),", mv.to_u16(), weight).unwrap();
+        }
+        writeln!(&mut book, "]),").unwrap();
+    }
+    writeln!(&mut book, "        _ => return None").unwrap();
+    writeln!(&mut book, "    }})").unwrap();
+    writeln!(&mut book, "}}").unwrap();
+
+    let mut startpos: PathBuf = std::env::var("OUT_DIR").unwrap().into();
+    startpos.push("startpos.rs");
+    std::fs::write(startpos, format!("{:?}", Board::default().to_packed())).unwrap();
+}
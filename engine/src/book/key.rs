@@ -0,0 +1,17 @@
+use cozy_chess::*;
+
+//The halfmove clock and fullmove number at the end of a packed board.
+const PACKED_CLOCKS_SIZE: usize = 3;
+
+//A second key for a book position, independent of its zobrist hash.
+//The book stores both, so a zobrist collision can't produce a false match.
+//This is FNV-1a over the packed board, ignoring the clocks since they don't affect book moves.
+pub fn book_key(board: &Board) -> u32 {
+    let packed = board.to_packed();
+    let mut key = 0x811C9DC5u32;
+    for &byte in &packed[..PACKED_BOARD_SIZE - PACKED_CLOCKS_SIZE] {
+        key ^= byte as u32;
+        key = key.wrapping_mul(0x01000193);
+    }
+    key
+}
@@ -0,0 +1,118 @@
+use cozy_chess::*;
+
+use crate::game::ChessGame;
+
+mod key;
+#[cfg(any(test, feature = "std"))]
+mod text;
+
+use key::book_key;
+#[cfg(any(test, feature = "std"))]
+pub use text::*;
+
+type BookEntry = (u32, &'static [(u16, u16)]);
+
+include!(concat!(env!("OUT_DIR"), "/book.rs"));
+
+/// Pick a book move with probability proportional to its weight.
+/// Returns `None` if the position isn't in the book.
+pub fn book_move(game: &ChessGame, random: u64) -> Option<Move> {
+    let board = game.board();
+    select_move(board, book_entry(board)?, random)
+}
+
+//The entry was found by zobrist hash alone, so confirm it with the second key
+//and only consider moves that are actually legal before trusting it.
+fn select_move(board: &Board, (key, moves): BookEntry, random: u64) -> Option<Move> {
+    if key != book_key(board) {
+        return None;
+    }
+    let legal_moves = || moves.iter().filter_map(|&(mv, weight)| {
+        let mv = Move::try_from_u16(mv)?;
+        if board.is_legal(mv) {
+            Some((mv, weight as u64))
+        } else {
+            None
+        }
+    });
+    let total: u64 = legal_moves().map(|(_, weight)| weight).sum();
+    if total == 0 {
+        return None;
+    }
+    let mut choice = random % total;
+    for (mv, weight) in legal_moves() {
+        if choice < weight {
+            return Some(mv);
+        }
+        choice -= weight;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn book_moves_are_legal() {
+        fn visit(board: &Board, depth: u8) {
+            let (key, moves) = match book_entry(board) {
+                Some(entry) => entry,
+                None => return
+            };
+            assert_eq!(key, book_key(board), "{}", board);
+            for &(mv, weight) in moves {
+                let mv = Move::from_u16(mv);
+                assert!(weight > 0, "{} {}", board, mv);
+                assert!(board.is_legal(mv), "{} {}", board, mv);
+                let mut child = board.clone();
+                child.play_unchecked(mv);
+                if depth > 0 {
+                    visit(&child, depth - 1);
+                }
+            }
+        }
+        visit(&Board::default(), 8);
+    }
+
+    #[test]
+    fn book_move_is_weighted() {
+        let game = ChessGame::new();
+        let (_, moves) = book_entry(game.board()).unwrap();
+        let total: u64 = moves.iter().map(|&(_, weight)| weight as u64).sum();
+        for &(mv, weight) in moves {
+            let picked = (0..total)
+                .filter(|&random| book_move(&game, random) == Some(Move::from_u16(mv)))
+                .count();
+            assert_eq!(picked as u64, weight as u64);
+        }
+    }
+
+    #[test]
+    fn mismatched_entries_are_rejected() {
+        let board = Board::default();
+        let key = book_key(&board);
+        const fn encode(from: Square, to: Square) -> u16 {
+            Move { from, to, promotion: None }.to_u16()
+        }
+        const E4: u16 = encode(Square::E2, Square::E4);
+        const E5: u16 = encode(Square::E7, Square::E5);
+        let moves = &[(E5, 100), (u16::MAX, 100), (E4, 1)];
+        // A collision with another position is caught by the second key.
+        assert_eq!(select_move(&board, (key ^ 1, moves), 0), None);
+        // Moves that are illegal or malformed here are skipped.
+        for random in 0..10 {
+            assert_eq!(select_move(&board, (key, moves), random), Some("e2e4".parse().unwrap()));
+        }
+        assert_eq!(select_move(&board, (key, &[(E5, 1)]), 0), None);
+    }
+
+    #[test]
+    fn book_key_ignores_clocks() {
+        let board: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".parse().unwrap();
+        let later: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 7 30".parse().unwrap();
+        let other: Board = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".parse().unwrap();
+        assert_eq!(book_key(&board), book_key(&later));
+        assert_ne!(book_key(&board), book_key(&other));
+    }
+}
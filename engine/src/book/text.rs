@@ -0,0 +1,184 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use cozy_chess::*;
+
+//Parsing for book.txt. The build script includes this to compile the book,
+//and native tools get it through the `std` feature to check the books they write.
+
+/// A position in a book and its moves, each with a weight.
+#[derive(Debug, Clone)]
+pub struct BookPosition {
+    pub board: Board,
+    pub moves: Vec<(Move, u32)>,
+    /// The line the position first appears on.
+    pub line: usize
+}
+
+/// A parsed book, keyed by the hash of each position.
+pub type Book = BTreeMap<u64, BookPosition>;
+
+fn add_move(book: &mut Book, board: &Board, mv: Move, weight: u32, line: usize) {
+    let position = book.entry(board.hash()).or_insert_with(|| BookPosition {
+        board: board.clone(),
+        moves: Vec::new(),
+        line
+    });
+    match position.moves.iter_mut().find(|(m, _)| *m == mv) {
+        Some((_, total)) => *total += weight,
+        None => position.moves.push((mv, weight))
+    }
+}
+
+//Parses `move` or `move:weight`. Moves may use standard castling notation.
+fn parse_weighted_move(board: &Board, s: &str, line: usize) -> Result<(Move, u32), String> {
+    let (mv, weight) = match s.split_once(':') {
+        Some((mv, weight)) => {
+            let weight = weight.parse()
+                .map_err(|_| format!("{}: invalid weight in {:?}", line, s))?;
+            if weight == 0 {
+                return Err(format!("{}: zero weight in {:?}", line, s));
+            }
+            (mv, weight)
+        }
+        None => (s, 1)
+    };
+    let mv = board.parse_move(mv)
+        .map_err(|_| format!("{}: invalid move {:?}", line, mv))?;
+    if !board.is_legal(mv) {
+        return Err(format!("{}: illegal move {} in {}", line, mv, board));
+    }
+    Ok((mv, weight))
+}
+
+//Book FENs only give an en passant square if a pawn can capture there,
+//but a board records one after every double pawn push. Both are the same position,
+//so reachability is checked with this hash, which ignores en passant squares nothing can capture on.
+fn position_hash(board: &Board) -> u64 {
+    let target = match board.en_passant() {
+        Some(file) => Square::new(file, Rank::Sixth.relative_to(board.side_to_move())),
+        None => return board.hash()
+    };
+    let mut capturable = false;
+    board.generate_moves(|moves| {
+        capturable |= moves.piece == Piece::Pawn && moves.into_iter().any(|mv| mv.to == target);
+        capturable
+    });
+    if capturable {
+        return board.hash();
+    }
+    let mut builder = BoardBuilder::from_board(board).unwrap();
+    builder.en_passant = None;
+    builder.build().unwrap().hash()
+}
+
+//Walks the book's moves from the start position, like the engine does when playing from it.
+//Any position that isn't reached can never be played from, so it's likely a mistake.
+fn check_reachable(book: &Book) -> Result<(), String> {
+    let mut positions = HashMap::<u64, Vec<&BookPosition>>::new();
+    for position in book.values() {
+        positions.entry(position_hash(&position.board)).or_default().push(position);
+    }
+    let mut reached = HashSet::new();
+    let mut queue = vec![position_hash(&Board::default())];
+    while let Some(hash) = queue.pop() {
+        if !reached.insert(hash) {
+            continue;
+        }
+        for position in positions.get(&hash).into_iter().flatten() {
+            for &(mv, _) in &position.moves {
+                let mut child = position.board.clone();
+                child.play_unchecked(mv);
+                queue.push(position_hash(&child));
+            }
+        }
+    }
+    let unreached = book.values()
+        .filter(|position| !reached.contains(&position_hash(&position.board)))
+        .min_by_key(|position| position.line);
+    match unreached {
+        Some(position) => Err(format!(
+            "{}: position {} is unreachable from the start position",
+            position.line,
+            position.board
+        )),
+        None => Ok(())
+    }
+}
+
+/// Parse a book in the book.txt format.
+/// Each line is either `FEN|move,move,...` or a sequence of moves from the start position,
+/// and any move may be given a weight with `move:weight`. `#` starts a comment.
+/// Every position must be reachable through the book's moves from the start position.
+/// Errors start with the number of the offending line.
+pub fn parse_book(text: &str) -> Result<Book, String> {
+    let mut book = Book::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some((board, moves)) = line.split_once('|') {
+            let board = board.trim().parse::<Board>()
+                .map_err(|_| format!("{}: invalid FEN {:?}", line_number, board))?;
+            for mv in moves.split(',') {
+                let (mv, weight) = parse_weighted_move(&board, mv.trim(), line_number)?;
+                add_move(&mut book, &board, mv, weight, line_number);
+            }
+        } else {
+            let mut board = Board::default();
+            for mv in line.split_whitespace() {
+                let (mv, weight) = parse_weighted_move(&board, mv, line_number)?;
+                add_move(&mut book, &board, mv, weight, line_number);
+                board.play_unchecked(mv);
+            }
+        }
+    }
+    check_reachable(&book)?;
+    Ok(book)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_and_positions_are_merged() {
+        let book = parse_book("\
+            g1f3:3 g8f6 # Reti
+            g1f3 d7d5:2
+            rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1|g8f6:4
+        ").unwrap();
+        assert_eq!(book.len(), 2);
+        let start = &book[&Board::default().hash()];
+        assert_eq!(start.moves, [("g1f3".parse().unwrap(), 4)]);
+        assert_eq!(start.line, 1);
+        let nf3: Board = "rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1".parse().unwrap();
+        let nf3 = &book[&nf3.hash()];
+        assert_eq!(nf3.moves, [("g8f6".parse().unwrap(), 5), ("d7d5".parse().unwrap(), 2)]);
+    }
+
+    #[test]
+    fn transpositions_are_reachable() {
+        //Only reached by 1. c4 Nf6 2. Nf3, but the book gives it as 1. Nf3 Nf6 2. c4.
+        parse_book("\
+            c2c4 g8f6 g1f3
+            rnbqkb1r/pppppppp/5n2/8/2P5/5N2/PP1PPPPP/RNBQKB1R b KQkq - 1 2|g7g6
+        ").unwrap();
+    }
+
+    #[test]
+    fn unreachable_positions_are_rejected() {
+        let error = parse_book("\
+            e2e4 e7e5
+            rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1|d7d5
+        ").unwrap_err();
+        assert!(error.starts_with("2: position"), "{}", error);
+    }
+
+    #[test]
+    fn invalid_moves_are_rejected() {
+        assert!(parse_book("e2e4 e7e5:0").unwrap_err().starts_with("1: zero weight"));
+        assert!(parse_book("e2e4\ne2e4 e2e4").unwrap_err().starts_with("2: illegal move"));
+    }
+}
@@ -0,0 +1,104 @@
+use heapless::Vec;
+use cozy_chess::*;
+
+#[derive(Debug, Clone)]
+pub struct ChessGame {
+    board: Board,
+    history: Vec<u64, 150>
+}
+
+//`Board::default` parses a FEN, which would pull the whole FEN parser into the cart.
+const STARTPOS: [u8; PACKED_BOARD_SIZE] = include!(concat!(env!("OUT_DIR"), "/startpos.rs"));
+
+impl ChessGame {
+    pub fn new() -> Self {
+        Self::from_board(Board::from_packed(&STARTPOS).unwrap())
+    }
+
+    pub fn from_board(board: Board) -> Self {
+        let mut history = Vec::new();
+        history.push(board.hash()).unwrap();
+        Self {
+            history,
+            board
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn history(&self) -> &[u64] {
+        &self.history
+    }
+
+    pub fn try_play(&mut self, mv: Move) -> bool {
+        if self.status() != GameStatus::Ongoing {
+            return false;
+        }
+        if !self.board.try_play(mv).unwrap() {
+            return false;
+        }
+        if self.board.halfmove_clock() == 0 {
+            self.history.clear();
+        }
+        self.history.push(self.board.hash()).unwrap();
+        true
+    }
+
+    pub fn status(&self) -> GameStatus {
+        let bishops = self.board.pieces(Piece::Bishop);
+        let knights = self.board.pieces(Piece::Knight);
+        match self.board.occupied().popcnt() {
+            2 => return GameStatus::Drawn,
+            3 if !(bishops | knights).is_empty() => return GameStatus::Drawn,
+            _ => {}
+        }
+        let current = *self.history.last().unwrap();
+        let repetitions = self.history.iter()
+            .filter(|&&h| h == current)
+            .count();
+        if repetitions >= 3 {
+            return GameStatus::Drawn;
+        }
+        self.board.status()
+    }
+}
+
+
+impl Default for ChessGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_game_is_startpos() {
+        assert_eq!(*ChessGame::new().board(), Board::default());
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let mut game = ChessGame::new();
+        for _ in 0..2 {
+            for mv in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                assert_eq!(game.status(), GameStatus::Ongoing);
+                assert!(game.try_play(mv.parse().unwrap()));
+            }
+        }
+        assert_eq!(game.status(), GameStatus::Drawn);
+        assert!(!game.try_play("e2e4".parse().unwrap()));
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let game = ChessGame::from_board("8/8/4k3/8/8/3NK3/8/8 w - - 0 1".parse().unwrap());
+        assert_eq!(game.status(), GameStatus::Drawn);
+        let game = ChessGame::from_board("8/8/4k3/8/8/3RK3/8/8 w - - 0 1".parse().unwrap());
+        assert_eq!(game.status(), GameStatus::Ongoing);
+    }
+}
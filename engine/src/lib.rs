@@ -0,0 +1,13 @@
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+mod eval;
+mod book;
+mod game;
+mod rng;
+mod search;
+
+pub use eval::evaluate;
+pub use book::*;
+pub use game::*;
+pub use rng::*;
+pub use search::*;
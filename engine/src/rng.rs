@@ -6,10 +6,16 @@ impl Rng {
         Self(0x7369787465656E2062797465206E756Du128 | 1)
     }
 
-    pub fn next(&mut self) -> u64 {
+    pub fn next_u64(&mut self) -> u64 {
         self.0 = self.0.wrapping_mul(0x2360ED051FC65DA44385DF649FCCF645);
         let rot = (self.0 >> 122) as u32;
         let xsl = ((self.0 >> 64) as u64) ^ (self.0 as u64);
         xsl.rotate_right(rot)     
     }
 }
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
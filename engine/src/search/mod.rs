@@ -0,0 +1,1033 @@
+use cozy_chess::*;
+
+use heapless::Vec;
+
+use crate::eval;
+use crate::book::book_move;
+use crate::game::ChessGame;
+
+mod tt;
+mod ordering;
+mod pv;
+
+pub use tt::*;
+pub use ordering::*;
+pub use pv::*;
+
+type History = Vec::<u64, 150>;
+
+pub const MATE_VALUE: i16 = 10_000;
+pub const MAX_DEPTH: u8 = 32;
+
+const INFINITY: i16 = 15_000;
+const ASPIRATION_WINDOW: i16 = 25;
+//How far the static eval of a frontier node must be below alpha for quiet moves to be skipped.
+const FUTILITY_MARGIN: i16 = 150;
+//The minimum depth to try a null move or reduce late moves at.
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const LMR_MIN_DEPTH: u8 = 3;
+//The number of moves searched at full depth before reducing.
+const LMR_MIN_MOVES: usize = 3;
+//How often the clock is read, since it may be expensive.
+const CLOCK_INTERVAL: u64 = 256;
+
+pub trait SearchHandler {
+    /// Called for every node. Returns `true` to stop the search.
+    fn stop(&mut self, nodes: u64) -> bool;
+
+    /// The milliseconds elapsed since the search started,
+    /// or `None` if there's no clock, in which case time limits are ignored.
+    fn elapsed_millis(&mut self) -> Option<u64> {
+        None
+    }
+
+    /// Called with the result of each completed iteration.
+    fn report(&mut self, _result: &SearchResult) {}
+}
+
+impl SearchHandler for () {
+    fn stop(&mut self, _: u64) -> bool {
+        false
+    }
+}
+
+/// Which selective search techniques to use.
+/// All of them are on by default; they can be switched off to measure their effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchFeatures {
+    /// Skip nodes where passing the turn still fails high.
+    pub null_move_pruning: bool,
+    /// Search quiet moves late in the move order to a reduced depth.
+    pub late_move_reductions: bool,
+    /// Skip quiet moves at frontier nodes that can't raise alpha.
+    pub futility_pruning: bool,
+    /// Search moves that give check one ply deeper.
+    pub check_extensions: bool,
+    /// Search moves after the first with a null window.
+    pub principal_variation_search: bool
+}
+
+impl SearchFeatures {
+    pub const ALL: Self = Self {
+        null_move_pruning: true,
+        late_move_reductions: true,
+        futility_pruning: true,
+        check_extensions: true,
+        principal_variation_search: true
+    };
+
+    /// A plain alpha-beta search.
+    pub const NONE: Self = Self {
+        null_move_pruning: false,
+        late_move_reductions: false,
+        futility_pruning: false,
+        check_extensions: false,
+        principal_variation_search: false
+    };
+}
+
+impl Default for SearchFeatures {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// The budget for [`search`]. The first iteration always completes,
+/// so there is a move to play even with a tiny budget.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    pub max_depth: u8,
+    pub max_nodes: Option<u64>,
+    pub max_millis: Option<u64>,
+    pub features: SearchFeatures,
+    /// The most that evaluations are randomly off by, to weaken the engine.
+    pub eval_noise: i16,
+    /// Seeds the noise. The same seed and position always get the same noise.
+    pub noise_seed: u64
+}
+
+impl SearchLimits {
+    pub const fn nodes(max_nodes: u64) -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_nodes: Some(max_nodes),
+            max_millis: None,
+            features: SearchFeatures::ALL,
+            eval_noise: 0,
+            noise_seed: 0
+        }
+    }
+
+    pub const fn millis(max_millis: u64) -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_nodes: None,
+            max_millis: Some(max_millis),
+            features: SearchFeatures::ALL,
+            eval_noise: 0,
+            noise_seed: 0
+        }
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_nodes: None,
+            max_millis: None,
+            features: SearchFeatures::ALL,
+            eval_noise: 0,
+            noise_seed: 0
+        }
+    }
+}
+
+/// The node budget the cart searches with.
+pub const DEFAULT_LIMITS: SearchLimits = SearchLimits::nodes(20_000);
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub mv: Move,
+    pub value: i16,
+    pub depth: u8,
+    /// The total nodes searched, including previous iterations.
+    pub nodes: u64,
+    /// The line the engine expects, starting with [`SearchResult::mv`].
+    pub pv: Vec<Move, MAX_PLY>
+}
+
+/// Search synchronously. See [`search`].
+/// This needs a large stack, so the cart uses [`Search`] instead.
+pub fn best_move(game: &ChessGame, random: u64, limits: SearchLimits) -> Move {
+    if let Some(mv) = book_move(game, random) {
+        return mv;
+    }
+    search(game, limits, &mut TranspositionTable::<TT_SIZE>::new(), &mut ()).unwrap().mv
+}
+
+/// Search with iterative deepening until the limits or the handler stop it,
+/// reporting each completed iteration to the handler.
+/// Returns the result of the last completed iteration,
+/// or `None` if the position has no legal moves.
+pub fn search<const N: usize>(
+    game: &ChessGame,
+    limits: SearchLimits,
+    tt: &mut TranspositionTable<N>,
+    handler: &mut impl SearchHandler
+) -> Option<SearchResult> {
+    let mut search = Search::new();
+    search.start(game, limits);
+    while !search.step(tt, u64::MAX, handler) {}
+    search.result()
+}
+
+/// Search to a fixed depth. Returns `None` if the position has no legal moves
+/// or the handler stopped the search before it completed.
+pub fn search_depth(game: &ChessGame, depth: u8, handler: &mut impl SearchHandler) -> Option<SearchResult> {
+    let depth = depth.clamp(1, MAX_DEPTH);
+    let mut search = Search::new();
+    search.start_at(game, SearchLimits {
+        max_depth: depth,
+        ..SearchLimits::default()
+    }, depth);
+    let mut tt = TranspositionTable::<TT_SIZE>::new();
+    while !search.step(&mut tt, u64::MAX, handler) {}
+    search.result().filter(|result| result.depth == depth)
+}
+
+fn evaluate(board: &Board, limits: &SearchLimits) -> i16 {
+    let eval = eval::evaluate(board);
+    let noise = limits.eval_noise;
+    if noise == 0 {
+        return eval;
+    }
+    //Noise derived from the position keeps the evaluation consistent within a search.
+    let random = (board.hash() ^ limits.noise_seed).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+    eval + (random % (2 * noise as u64 + 1)) as i16 - noise
+}
+
+/// The deepest ply searched. Positions this deep are evaluated statically.
+///
+/// Native builds have room for the deepest search plus the quiescence search below it.
+/// The cart keeps its original cap of 20 plies, which keeps the search stack within its RAM.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_PLY: usize = MAX_DEPTH as usize + 20;
+/// The deepest ply searched. Positions this deep are evaluated statically.
+//The cart keeps its original cap, which keeps the search stack within its RAM.
+#[cfg(target_arch = "wasm32")]
+pub const MAX_PLY: usize = 20;
+//Moves of every node on the stack share one buffer, packed with `Move::to_u16`.
+const MAX_STACK_MOVES: usize = 768;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Search,
+    QSearch
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    //Takes back the move leading to this frame. The root and null moves have none.
+    undo: Option<Undo>,
+    hash: u64,
+    kind: NodeKind,
+    depth: u8,
+    //The window the frame was searched with. Alpha is raised as better moves are found.
+    start_alpha: i16,
+    alpha: i16,
+    beta: i16,
+    best_value: i16,
+    best_move: Option<Move>,
+    //How much shallower than usual the frame is searched.
+    reduction: u8,
+    static_eval: i16,
+    //The move whose subtree is on the stack above this frame, or `None` for a null move.
+    current_move: Option<Move>,
+    //Where this frame's moves start in the move buffer.
+    moves_start: usize,
+    next_move: usize,
+    expanded: bool,
+    //Set by `expand` if a null move should be tried before any moves.
+    try_null_move: bool
+}
+
+/// A resumable search. The cart can't afford to search recursively
+/// or to finish a search in a single frame, so the search keeps
+/// its own stack and runs a bounded number of nodes per [`Search::step`].
+///
+/// This is large, so it should live in a `static`. [`Search::new`] is `const` for that reason.
+//An idle search is all zeros, which keeps such a `static` out of the cart's data section.
+//For the same reason the root is kept packed and the board is rebuilt on every step.
+pub struct Search {
+    root: [u8; PACKED_BOARD_SIZE],
+    history: History,
+    limits: SearchLimits,
+    stack: Vec<Frame, MAX_PLY>,
+    moves: Vec<(u16, i16), MAX_STACK_MOVES>,
+    ordering: MoveOrdering,
+    pv: PvTable,
+    depth: u8,
+    alpha: i16,
+    beta: i16,
+    window: i16,
+    nodes: u64,
+    completed: Completed,
+    running: bool
+}
+
+//The last completed iteration. A depth of zero means there is none.
+#[derive(Debug, Clone, Copy)]
+struct Completed {
+    pv: [u16; MAX_PLY],
+    pv_len: u8,
+    value: i16,
+    depth: u8,
+    nodes: u64
+}
+
+impl Completed {
+    const NONE: Self = Self {
+        pv: [0; MAX_PLY],
+        pv_len: 0,
+        value: 0,
+        depth: 0,
+        nodes: 0
+    };
+
+    fn result(&self) -> Option<SearchResult> {
+        if self.depth == 0 {
+            return None;
+        }
+        let pv = self.pv[..self.pv_len as usize].iter().map(|&mv| Move::from_u16(mv)).collect();
+        Some(SearchResult {
+            mv: Move::from_u16(self.pv[0]),
+            value: self.value,
+            depth: self.depth,
+            nodes: self.nodes,
+            pv
+        })
+    }
+}
+
+impl Search {
+    /// Create an idle search. Call [`Search::start`] to begin searching.
+    pub const fn new() -> Self {
+        Self {
+            root: [0; PACKED_BOARD_SIZE],
+            history: Vec::new(),
+            limits: SearchLimits {
+                max_depth: 0,
+                max_nodes: None,
+                max_millis: None,
+                features: SearchFeatures::NONE,
+                eval_noise: 0,
+                noise_seed: 0
+            },
+            stack: Vec::new(),
+            moves: Vec::new(),
+            ordering: MoveOrdering::new(),
+            pv: PvTable::new(),
+            depth: 0,
+            alpha: 0,
+            beta: 0,
+            window: 0,
+            nodes: 0,
+            completed: Completed::NONE,
+            running: false
+        }
+    }
+
+    /// Start searching a position, abandoning any previous search.
+    pub fn start(&mut self, game: &ChessGame, limits: SearchLimits) {
+        self.start_at(game, limits, 1);
+    }
+
+    fn start_at(&mut self, game: &ChessGame, limits: SearchLimits, depth: u8) {
+        self.root = game.board().to_packed();
+        self.history.clear();
+        self.history.extend_from_slice(game.history()).unwrap();
+        self.history.pop();
+        self.limits = limits;
+        self.limits.max_depth = limits.max_depth.clamp(1, MAX_DEPTH);
+        self.stack.clear();
+        self.moves.clear();
+        self.ordering.new_search();
+        //The root frame stays at the bottom of the stack for the whole search.
+        self.push_frame(None, game.board().hash(), NodeKind::Search, 0, -INFINITY, INFINITY);
+        self.depth = depth - 1;
+        self.nodes = 0;
+        self.completed = Completed::NONE;
+        self.running = true;
+        self.start_iteration(&mut ());
+    }
+
+    /// The result of the last completed iteration, if any.
+    pub fn result(&self) -> Option<SearchResult> {
+        self.completed.result()
+    }
+
+    /// Check if the search has finished. Idle searches are finished.
+    pub fn is_finished(&self) -> bool {
+        !self.running
+    }
+
+    /// Continue the search for at most `nodes` nodes.
+    /// The same table should be used for every step of a search.
+    /// Returns `true` once the search has finished.
+    pub fn step<const N: usize>(
+        &mut self,
+        tt: &mut TranspositionTable<N>,
+        nodes: u64,
+        handler: &mut impl SearchHandler
+    ) -> bool {
+        if !self.running {
+            return true;
+        }
+        if self.nodes == 0 {
+            tt.new_search();
+        }
+        let mut board = self.replay();
+        let mut remaining = nodes;
+        while self.running {
+            let top = self.stack.len() - 1;
+            if !self.stack[top].expanded {
+                if remaining == 0 {
+                    return false;
+                }
+                remaining -= 1;
+                if self.visit_node(handler) {
+                    //Stopped; the unfinished iteration is thrown away.
+                    self.stack.truncate(1);
+                    self.moves.clear();
+                    self.running = false;
+                } else if let Some(value) = self.expand(tt, &board, top) {
+                    self.return_value(tt, &mut board, value, handler);
+                }
+            } else if self.stack[top].try_null_move {
+                self.push_null_move(&mut board, top);
+            } else if let Some(mv) = self.next_move(top) {
+                self.push_move(&mut board, top, mv);
+            } else {
+                let value = self.stack[top].best_value;
+                self.return_value(tt, &mut board, value, handler);
+            }
+        }
+        true
+    }
+
+    //Rebuilds the board at the top of the stack from the root.
+    fn replay(&mut self) -> Board {
+        let mut board = Board::from_packed(&self.root).unwrap();
+        for ply in 1..self.stack.len() {
+            self.stack[ply].undo = match self.stack[ply - 1].current_move {
+                Some(mv) => Some(board.play_with_undo(mv)),
+                None => {
+                    board = board.null_move().unwrap();
+                    None
+                }
+            };
+        }
+        board
+    }
+
+    fn out_of_budget(&mut self, handler: &mut impl SearchHandler) -> bool {
+        if self.limits.max_nodes.is_some_and(|max_nodes| self.nodes >= max_nodes) {
+            return true;
+        }
+        if let Some(max_millis) = self.limits.max_millis {
+            if self.nodes.is_multiple_of(CLOCK_INTERVAL) {
+                return handler.elapsed_millis().is_some_and(|elapsed| elapsed >= max_millis);
+            }
+        }
+        false
+    }
+
+    fn visit_node(&mut self, handler: &mut impl SearchHandler) -> bool {
+        self.nodes += 1;
+        //The first iteration always completes so that there's a move to play.
+        self.depth > 1 && (handler.stop(self.nodes) || self.out_of_budget(handler))
+    }
+
+    fn start_iteration(&mut self, handler: &mut impl SearchHandler) {
+        if self.depth >= self.limits.max_depth {
+            self.running = false;
+            return;
+        }
+        //Another iteration takes longer than all previous ones, so don't start one that can't finish.
+        if let Some(max_millis) = self.limits.max_millis {
+            if self.depth > 1 && handler.elapsed_millis().is_some_and(|elapsed| elapsed >= max_millis / 2) {
+                self.running = false;
+                return;
+            }
+        }
+        self.depth += 1;
+        self.window = ASPIRATION_WINDOW;
+        if self.completed.depth > 0 && self.depth >= 4 {
+            let prev = self.completed.value;
+            self.alpha = prev.saturating_sub(self.window).max(-INFINITY);
+            self.beta = prev.saturating_add(self.window).min(INFINITY);
+        } else {
+            self.alpha = -INFINITY;
+            self.beta = INFINITY;
+        }
+        self.reset_root();
+    }
+
+    fn reset_root(&mut self) {
+        self.moves.clear();
+        let root = &mut self.stack[0];
+        root.kind = NodeKind::Search;
+        root.depth = self.depth;
+        root.start_alpha = self.alpha;
+        root.alpha = self.alpha;
+        root.beta = self.beta;
+        root.best_value = -INFINITY;
+        root.best_move = None;
+        root.current_move = None;
+        root.next_move = 0;
+        root.expanded = false;
+        root.try_null_move = false;
+        self.pv.clear(0);
+    }
+
+    fn finish_iteration(&mut self, mv: Option<Move>, value: i16, handler: &mut impl SearchHandler) {
+        //Search a narrow window around the previous iteration's score,
+        //widening it on the side that failed until the score lands inside.
+        if (value <= self.alpha && self.alpha > -INFINITY) || (value >= self.beta && self.beta < INFINITY) {
+            self.window = self.window.saturating_mul(2);
+            if value <= self.alpha {
+                self.alpha = value.saturating_sub(self.window).max(-INFINITY);
+            } else {
+                self.beta = value.saturating_add(self.window).min(INFINITY);
+            }
+            self.reset_root();
+            return;
+        }
+        if mv.is_none() {
+            //No legal moves at the root.
+            self.running = false;
+            return;
+        }
+        let mut completed = Completed {
+            value,
+            depth: self.depth,
+            nodes: self.nodes,
+            ..Completed::NONE
+        };
+        for (slot, mv) in completed.pv.iter_mut().zip(self.pv.line(0)) {
+            *slot = mv.to_u16();
+            completed.pv_len += 1;
+        }
+        self.completed = completed;
+        handler.report(&self.completed.result().unwrap());
+        if value.abs() >= MATE_VALUE - self.depth as i16 {
+            //A forced mate was found within the full width of the search.
+            self.running = false;
+        } else {
+            self.start_iteration(handler);
+        }
+    }
+
+    fn push_frame(&mut self, undo: Option<Undo>, hash: u64, kind: NodeKind, depth: u8, alpha: i16, beta: i16) {
+        let frame = Frame {
+            undo,
+            hash,
+            kind,
+            depth,
+            start_alpha: alpha,
+            alpha,
+            beta,
+            best_value: -INFINITY,
+            best_move: None,
+            reduction: 0,
+            static_eval: 0,
+            current_move: None,
+            moves_start: self.moves.len(),
+            next_move: self.moves.len(),
+            expanded: false,
+            try_null_move: false
+        };
+        //Frames past the last ply are never pushed, since `expand` makes them leaves.
+        self.stack.push(frame).unwrap();
+        self.pv.clear(self.stack.len() - 1);
+    }
+
+    fn push_null_move(&mut self, board: &mut Board, parent: usize) {
+        let frame = &mut self.stack[parent];
+        frame.try_null_move = false;
+        frame.current_move = None;
+        //Deeper searches can afford to reduce more.
+        let reduction = if frame.depth >= 7 { 3 } else { 2 };
+        let (depth, beta) = (frame.depth.saturating_sub(1 + reduction), frame.beta);
+        *board = board.null_move().unwrap();
+        self.push_frame(None, board.hash(), NodeKind::Search, depth, -beta, -beta + 1);
+    }
+
+    fn push_move(&mut self, board: &mut Board, parent: usize, mv: Move) {
+        let features = self.limits.features;
+        let frame = &mut self.stack[parent];
+        frame.current_move = Some(mv);
+        let quiet = is_quiet(board, mv) && board.checkers().is_empty();
+        let undo = board.play_with_undo(mv);
+        let hash = board.hash();
+        if frame.kind == NodeKind::QSearch {
+            let (alpha, beta) = (frame.alpha, frame.beta);
+            self.push_frame(Some(undo), hash, NodeKind::QSearch, 0, -beta, -alpha);
+            return;
+        }
+        let gives_check = !board.checkers().is_empty();
+        let quiet = quiet && !gives_check;
+        let searched = frame.next_move - frame.moves_start - 1;
+        //A quiet move is unlikely to make up for a large deficit this close to the quiescence search.
+        let futile = frame.depth == 1 && frame.static_eval.saturating_add(FUTILITY_MARGIN) <= frame.alpha;
+        if features.futility_pruning && futile && quiet && searched > 0 {
+            board.unplay(undo);
+            return;
+        }
+        let depth = frame.depth - 1 + (features.check_extensions && gives_check) as u8;
+        //Late moves are unlikely to be good if the move ordering did its job.
+        //If one turns out to be, it's searched again without the reduction.
+        let reduction = if features.late_move_reductions
+            && frame.depth >= LMR_MIN_DEPTH
+            && searched >= LMR_MIN_MOVES
+            && quiet {
+            (if searched >= 2 * LMR_MIN_MOVES { 2 } else { 1 }).min(depth - 1)
+        } else {
+            0
+        };
+        //Only prove that later moves are worse than the best so far,
+        //searching them again with the full window if they aren't.
+        let (alpha, beta) = if features.principal_variation_search && searched > 0 {
+            (frame.alpha, frame.alpha + 1)
+        } else {
+            (frame.alpha, frame.beta)
+        };
+        self.push_frame(Some(undo), hash, NodeKind::Search, depth - reduction, -beta, -alpha);
+        self.stack.last_mut().unwrap().reduction = reduction;
+    }
+
+    fn repetitions(&self, ply: usize) -> usize {
+        let hash = self.stack[ply].hash;
+        let mut count = 0;
+        for (i, frame) in self.stack[..=ply].iter().enumerate().rev() {
+            if i < ply && frame.hash == hash {
+                count += 1;
+            }
+            //Positions before a null move can't be repeated after it.
+            if frame.undo.is_none() {
+                if i == 0 {
+                    count += self.history.iter().filter(|&&h| h == hash).count();
+                }
+                break;
+            }
+        }
+        count
+    }
+
+    //Prepares a frame's moves. Returns the frame's value instead if it's a leaf.
+    fn expand<const N: usize>(&mut self, tt: &TranspositionTable<N>, board: &Board, ply: usize) -> Option<i16> {
+        match board.status() {
+            GameStatus::Won => return Some(-MATE_VALUE + ply as i16),
+            GameStatus::Drawn => return Some(0),
+            GameStatus::Ongoing => {}
+        }
+        if self.stack[ply].kind == NodeKind::Search {
+            if self.repetitions(ply) >= 2 {
+                return Some(0);
+            }
+            if self.stack[ply].depth == 0 {
+                self.stack[ply].kind = NodeKind::QSearch;
+            }
+        }
+        if ply + 1 >= MAX_PLY {
+            return Some(evaluate(board, &self.limits));
+        }
+        let frame = &mut self.stack[ply];
+        let mut hash_move = None;
+        if frame.kind == NodeKind::Search {
+            if let Some(entry) = tt.probe(frame.hash, ply) {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.value >= frame.beta,
+                    Bound::Upper => entry.value <= frame.alpha
+                };
+                if ply > 0 && entry.depth >= frame.depth && cutoff {
+                    return Some(entry.value);
+                }
+                //The entry may belong to another position, so the move has to be checked.
+                hash_move = entry.mv.filter(|&mv| board.is_legal(mv));
+            }
+            if board.checkers().is_empty() {
+                frame.static_eval = evaluate(board, &self.limits);
+                //Passing is only a safe bet with pieces besides pawns, as zugzwang is likely otherwise.
+                let pawns_and_kings = board.pieces(Piece::Pawn) | board.pieces(Piece::King);
+                let has_pieces = !(board.colors(board.side_to_move()) & !pawns_and_kings).is_empty();
+                //Two null moves in a row would just search the same position shallower.
+                frame.try_null_move = self.limits.features.null_move_pruning
+                    && frame.undo.is_some()
+                    && frame.depth >= NULL_MOVE_MIN_DEPTH
+                    && frame.static_eval >= frame.beta
+                    && frame.beta.abs() < MATE_VALUE - MAX_PLY as i16
+                    && has_pieces;
+            }
+        }
+        let ordering = &self.ordering;
+        let overflowed = match frame.kind {
+            NodeKind::Search => board.generate_moves(|moves| {
+                moves.into_iter().any(|mv| {
+                    let score = ordering.score(board, mv, hash_move, ply);
+                    self.moves.push((mv.to_u16(), score)).is_err()
+                })
+            }),
+            NodeKind::QSearch => {
+                let eval = evaluate(board, &self.limits);
+                frame.best_value = eval;
+                frame.alpha = frame.alpha.max(eval);
+                if frame.alpha >= frame.beta {
+                    return Some(eval);
+                }
+                board.generate_tactical_moves(|moves| {
+                    moves.into_iter().any(|mv| {
+                        //Underpromotions are never worth searching here.
+                        if matches!(mv.promotion, Some(piece) if piece != Piece::Queen) {
+                            return false;
+                        }
+                        let score = ordering.score(board, mv, None, ply);
+                        self.moves.push((mv.to_u16(), score)).is_err()
+                    })
+                })
+            }
+        };
+        if overflowed {
+            self.moves.truncate(frame.moves_start);
+            return Some(evaluate(board, &self.limits));
+        }
+        frame.expanded = true;
+        None
+    }
+
+    //Picks the highest scoring move that hasn't been searched yet.
+    fn next_move(&mut self, ply: usize) -> Option<Move> {
+        let frame = &mut self.stack[ply];
+        let remaining = &mut self.moves[frame.next_move..];
+        let best = remaining.iter()
+            .enumerate()
+            .max_by_key(|&(i, &(_, score))| (score, core::cmp::Reverse(i)))?
+            .0;
+        remaining.swap(0, best);
+        frame.next_move += 1;
+        Some(Move::from_u16(remaining[0].0))
+    }
+
+    fn store<const N: usize>(&self, tt: &mut TranspositionTable<N>, ply: usize, value: i16) {
+        let frame = &self.stack[ply];
+        //Leaves and the quiescence search aren't worth the space.
+        if frame.kind != NodeKind::Search || !frame.expanded {
+            return;
+        }
+        let bound = if value >= frame.beta {
+            Bound::Lower
+        } else if value > frame.start_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        tt.store(frame.hash, ply, TtEntry {
+            depth: frame.depth,
+            bound,
+            value,
+            //No move stood out when every move failed low.
+            mv: if bound == Bound::Upper { None } else { frame.best_move }
+        });
+    }
+
+    //Pops frames until one doesn't fail high, passing each value down to the parent.
+    fn return_value<const N: usize>(
+        &mut self,
+        tt: &mut TranspositionTable<N>,
+        board: &mut Board,
+        mut value: i16,
+        handler: &mut impl SearchHandler
+    ) {
+        loop {
+            self.store(tt, self.stack.len() - 1, value);
+            if self.stack.len() == 1 {
+                let best_move = self.stack[0].best_move;
+                self.finish_iteration(best_move, value, handler);
+                return;
+            }
+            let frame = self.stack.pop().unwrap();
+            self.moves.truncate(frame.moves_start);
+            let ply = self.stack.len() - 1;
+            value = -value;
+            let undo = match frame.undo {
+                Some(undo) => undo,
+                None => {
+                    //Null moves can't be taken back, so the board is rebuilt instead.
+                    *board = self.replay();
+                    let beta = self.stack[ply].beta;
+                    if value >= beta {
+                        //Passing can't really lead to mate.
+                        if value >= MATE_VALUE - MAX_PLY as i16 {
+                            value = beta;
+                        }
+                        continue;
+                    }
+                    return;
+                }
+            };
+            board.unplay(undo);
+            let parent = &mut self.stack[ply];
+            let null_window = frame.start_alpha > -parent.beta;
+            if value > parent.alpha && (frame.reduction > 0 || (null_window && value < parent.beta)) {
+                //Search the move again, first without the reduction, then with the full window.
+                let alpha = if frame.reduction > 0 { frame.start_alpha } else { -parent.beta };
+                let undo = board.play_with_undo(undo.mv());
+                self.push_frame(Some(undo), board.hash(), NodeKind::Search, frame.depth + frame.reduction, alpha, frame.beta);
+                return;
+            }
+            if value > parent.best_value {
+                parent.best_value = value;
+                parent.best_move = parent.current_move;
+                if value > parent.alpha {
+                    parent.alpha = value;
+                    self.pv.update(ply, undo.mv());
+                    if value >= parent.beta {
+                        let mv = undo.mv();
+                        if parent.kind == NodeKind::Search && undo.victim().is_none() && mv.promotion.is_none() {
+                            self.ordering.update(board, mv, ply, parent.depth);
+                        }
+                        continue;
+                    }
+                }
+            }
+            return;
+        }
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(fen: &str) -> ChessGame {
+        ChessGame::from_board(fen.parse().unwrap())
+    }
+
+    fn tt() -> TranspositionTable {
+        TranspositionTable::new()
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let game = game("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        for depth in 1..=3 {
+            let result = search_depth(&game, depth, &mut ()).unwrap();
+            assert_eq!(result.mv, "a1a8".parse().unwrap());
+            assert_eq!(result.value, MATE_VALUE - 1);
+        }
+    }
+
+    #[test]
+    fn handler_stops_search() {
+        struct NodeLimit(u64);
+
+        impl SearchHandler for NodeLimit {
+            fn stop(&mut self, nodes: u64) -> bool {
+                nodes >= self.0
+            }
+        }
+
+        let game = ChessGame::new();
+        let result = search_depth(&game, 3, &mut NodeLimit(u64::MAX)).unwrap();
+        assert!(search_depth(&game, 3, &mut NodeLimit(result.nodes)).is_none());
+        assert!(search_depth(&game, 3, &mut NodeLimit(result.nodes + 1)).is_some());
+    }
+
+    #[test]
+    fn no_moves_when_game_over() {
+        let game = game("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1");
+        assert!(search_depth(&game, 2, &mut ()).is_none());
+        assert!(search(&game, SearchLimits::default(), &mut tt(), &mut ()).is_none());
+    }
+
+    #[test]
+    fn search_is_resumable() {
+        let game = game("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let limits = SearchLimits {
+            max_depth: 4,
+            ..SearchLimits::default()
+        };
+        let expected = search(&game, limits, &mut tt(), &mut ()).unwrap();
+        for nodes_per_step in [1, 7, 1000] {
+            let mut search = Search::new();
+            search.start(&game, limits);
+            let mut steps = 0;
+            let mut tt = tt();
+            while !search.step(&mut tt, nodes_per_step, &mut ()) {
+                steps += 1;
+            }
+            let result = search.result().unwrap();
+            assert!(search.is_finished());
+            assert_eq!((result.mv, result.value, result.nodes), (expected.mv, expected.value, expected.nodes));
+            assert!(steps >= expected.nodes / nodes_per_step - 1);
+        }
+    }
+
+    #[derive(Default)]
+    struct Reports(std::vec::Vec<SearchResult>);
+
+    impl SearchHandler for Reports {
+        fn stop(&mut self, _: u64) -> bool {
+            false
+        }
+
+        fn report(&mut self, result: &SearchResult) {
+            self.0.push(result.clone());
+        }
+    }
+
+    #[test]
+    fn iterative_deepening_reports_iterations() {
+        let game = ChessGame::new();
+        let limits = SearchLimits {
+            max_depth: 4,
+            ..SearchLimits::default()
+        };
+        let mut reports = Reports::default();
+        let result = search(&game, limits, &mut tt(), &mut reports).unwrap();
+        let depths: std::vec::Vec<_> = reports.0.iter().map(|r| r.depth).collect();
+        assert_eq!(depths, [1, 2, 3, 4]);
+        assert!(reports.0.windows(2).all(|w| w[0].nodes < w[1].nodes));
+        //Aspiration windows must not change the value of a full width search.
+        //Equally good moves may be ordered differently, so the move can differ.
+        let fixed = search_depth(&game, 4, &mut ()).unwrap();
+        assert_eq!(result.value, fixed.value);
+    }
+
+    #[test]
+    fn iterative_deepening_respects_node_limit() {
+        let game = game("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let mut reports = Reports::default();
+        let result = search(&game, SearchLimits::nodes(5_000), &mut tt(), &mut reports).unwrap();
+        let last = reports.0.last().unwrap();
+        assert_eq!((result.mv, result.depth), (last.mv, last.depth));
+        assert!(result.nodes <= 5_000);
+        //The first iteration completes regardless of the budget.
+        let result = search(&game, SearchLimits::nodes(1), &mut tt(), &mut ()).unwrap();
+        assert_eq!(result.depth, 1);
+    }
+
+    #[test]
+    fn iterative_deepening_stops_at_mate() {
+        let game = game("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1");
+        let result = search(&game, SearchLimits::default(), &mut tt(), &mut ()).unwrap();
+        assert_eq!(result.mv, "a1a8".parse().unwrap());
+        assert!(result.depth <= 2);
+    }
+
+    #[test]
+    fn transposition_table_persists_between_searches() {
+        let game = game("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let limits = SearchLimits {
+            max_depth: 4,
+            ..SearchLimits::default()
+        };
+        let mut tt = tt();
+        let first = search(&game, limits, &mut tt, &mut ()).unwrap();
+        let second = search(&game, limits, &mut tt, &mut ()).unwrap();
+        assert_eq!(first.mv, second.mv);
+        assert!(second.nodes < first.nodes);
+    }
+
+    #[test]
+    fn features_are_switchable() {
+        let game = game("r2q1rk1/pp2bppp/2n1bn2/3p4/3P4/2NBBN2/PP3PPP/R2Q1RK1 w - - 4 10");
+        let nodes = |features| {
+            let limits = SearchLimits {
+                max_depth: 5,
+                features,
+                ..SearchLimits::default()
+            };
+            search(&game, limits, &mut tt(), &mut ()).unwrap().nodes
+        };
+        let all = nodes(SearchFeatures::ALL);
+        assert!(all < nodes(SearchFeatures::NONE) / 2);
+        let switches: [fn(&mut SearchFeatures) -> &mut bool; 5] = [
+            |f| &mut f.null_move_pruning,
+            |f| &mut f.late_move_reductions,
+            |f| &mut f.futility_pruning,
+            |f| &mut f.check_extensions,
+            |f| &mut f.principal_variation_search
+        ];
+        for switch in switches {
+            let mut features = SearchFeatures::ALL;
+            *switch(&mut features) = false;
+            assert_ne!(nodes(features), all);
+        }
+    }
+
+    #[test]
+    fn no_null_moves_in_pawn_endings() {
+        let game = game("8/5k2/3p2p1/2pP1p2/2P2P2/4K1P1/8/8 w - - 0 1");
+        let nodes = |null_move_pruning| {
+            let limits = SearchLimits {
+                max_depth: 8,
+                features: SearchFeatures {
+                    null_move_pruning,
+                    ..SearchFeatures::ALL
+                },
+                ..SearchLimits::default()
+            };
+            search(&game, limits, &mut tt(), &mut ()).unwrap().nodes
+        };
+        assert_eq!(nodes(true), nodes(false));
+    }
+
+    #[test]
+    fn eval_noise_is_seeded() {
+        let game = ChessGame::new();
+        let result = |noise_seed| {
+            let limits = SearchLimits {
+                max_depth: 3,
+                eval_noise: 200,
+                noise_seed,
+                ..SearchLimits::default()
+            };
+            let result = search(&game, limits, &mut tt(), &mut ()).unwrap();
+            (result.mv, result.value)
+        };
+        assert_eq!(result(1), result(1));
+        assert!((2..10).any(|seed| result(seed) != result(1)));
+    }
+
+    #[test]
+    fn pv_is_a_legal_line() {
+        let opening = game("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let mut reports = Reports::default();
+        search(&opening, SearchLimits::nodes(20_000), &mut tt(), &mut reports).unwrap();
+        for result in reports.0 {
+            assert_eq!(result.pv.first(), Some(&result.mv));
+            let mut board = opening.board().clone();
+            for &mv in &result.pv {
+                assert!(board.try_play(mv).unwrap(), "{} in {:?}", mv, result.pv);
+            }
+        }
+        //Mate in two: the whole mating line is known.
+        let mate = game("1r4k1/5ppp/8/8/8/4R3/5PPP/4R1K1 w - - 0 1");
+        let result = search(&mate, SearchLimits::default(), &mut tt(), &mut ()).unwrap();
+        assert_eq!(result.pv.len(), 3);
+        let mut board = mate.board().clone();
+        for &mv in &result.pv {
+            board.play(mv);
+        }
+        assert_eq!(board.status(), GameStatus::Won);
+    }
+}
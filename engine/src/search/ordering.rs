@@ -0,0 +1,168 @@
+use cozy_chess::*;
+
+use super::MAX_PLY;
+
+//Scores are bands, so that each kind of move is always tried before the next.
+const HASH_MOVE: i16 = i16::MAX;
+const CAPTURE: i16 = 20_000;
+const PROMOTION: i16 = 19_000;
+const KILLER: i16 = 18_000;
+//History scores stay within this, below the killers.
+const HISTORY_MAX: i16 = 16_000;
+const UNDERPROMOTION: i16 = -20_000;
+
+/// Scores moves so that the ones most likely to cause a cutoff are searched first.
+///
+/// The hash move comes first, then captures by most valuable victim and least valuable attacker,
+/// then queen promotions, then the killer moves that caused cutoffs at the same ply,
+/// then other quiet moves by how often they caused cutoffs anywhere.
+//All zeros when cleared, like the search that owns it.
+//History is kept by piece and destination rather than by origin and destination,
+//which is a fraction of the size and matters on the cart.
+pub struct MoveOrdering {
+    killers: [[u16; 2]; MAX_PLY],
+    history: [[i16; Square::NUM]; Color::NUM * Piece::NUM]
+}
+
+//A move is never from a square to itself, so zero means there's no killer.
+const NO_KILLER: u16 = 0;
+
+fn history_index(board: &Board, mv: Move) -> usize {
+    let piece = board.piece_on(mv.from).unwrap();
+    board.side_to_move() as usize * Piece::NUM + piece as usize
+}
+
+fn victim(board: &Board, mv: Move) -> Option<Piece> {
+    if board.colors(!board.side_to_move()).has(mv.to) {
+        return board.piece_on(mv.to);
+    }
+    //A pawn moving diagonally onto an empty square is capturing en passant.
+    let is_pawn = board.pieces(Piece::Pawn).has(mv.from);
+    if is_pawn && mv.from.file() != mv.to.file() {
+        Some(Piece::Pawn)
+    } else {
+        None
+    }
+}
+
+/// Check if a move is neither a capture nor a promotion.
+pub fn is_quiet(board: &Board, mv: Move) -> bool {
+    mv.promotion.is_none() && victim(board, mv).is_none()
+}
+
+impl MoveOrdering {
+    pub const fn new() -> Self {
+        Self {
+            killers: [[NO_KILLER; 2]; MAX_PLY],
+            history: [[0; Square::NUM]; Color::NUM * Piece::NUM]
+        }
+    }
+
+    /// Prepare for a new search. Killers are forgotten, since plies now refer to other positions,
+    /// but history is only faded, since it mostly still applies.
+    pub fn new_search(&mut self) {
+        self.killers.fill([NO_KILLER; 2]);
+        for score in self.history.iter_mut().flatten() {
+            *score /= 2;
+        }
+    }
+
+    /// Score a legal move in `board`, which is `ply` plies from the root. Higher is better.
+    pub fn score(&self, board: &Board, mv: Move, hash_move: Option<Move>, ply: usize) -> i16 {
+        if Some(mv) == hash_move {
+            return HASH_MOVE;
+        }
+        if matches!(mv.promotion, Some(piece) if piece != Piece::Queen) {
+            return UNDERPROMOTION;
+        }
+        if let Some(victim) = victim(board, mv) {
+            let attacker = board.piece_on(mv.from).unwrap();
+            return CAPTURE + (victim as i16 + 1) * 8 - attacker as i16;
+        }
+        if mv.promotion.is_some() {
+            return PROMOTION;
+        }
+        let killers = self.killers[ply];
+        let mv_bits = mv.to_u16();
+        if killers[0] == mv_bits {
+            KILLER
+        } else if killers[1] == mv_bits {
+            KILLER - 1
+        } else {
+            self.history[history_index(board, mv)][mv.to as usize]
+        }
+    }
+
+    /// Record a quiet move in `board` that caused a cutoff at `ply` with `depth` plies left to search.
+    pub fn update(&mut self, board: &Board, mv: Move, ply: usize, depth: u8) {
+        let killers = &mut self.killers[ply];
+        if killers[0] != mv.to_u16() {
+            killers[1] = killers[0];
+            killers[0] = mv.to_u16();
+        }
+        //Deeper cutoffs are more reliable. The score approaches the maximum
+        //more slowly the closer it gets, so it never leaves the history band.
+        let bonus = (depth as i32 * depth as i32).min(HISTORY_MAX as i32);
+        let score = &mut self.history[history_index(board, mv)][mv.to as usize];
+        *score += (bonus - *score as i32 * bonus / HISTORY_MAX as i32) as i16;
+    }
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_kinds_are_ordered() {
+        let board: Board = "r3k3/1P6/8/3p4/4P3/2N5/8/4K2R w K d6 0 1".parse().unwrap();
+        let mut ordering = MoveOrdering::new();
+        ordering.update(&board, "h1h2".parse().unwrap(), 1, 3);
+        ordering.update(&board, "e1f2".parse().unwrap(), 1, 1);
+        let score = |ordering: &MoveOrdering, mv: &str, hash_move: Option<&str>| {
+            let hash_move = hash_move.map(|mv| mv.parse().unwrap());
+            ordering.score(&board, mv.parse().unwrap(), hash_move, 1)
+        };
+        let ordered = [
+            ("e1d1", Some("e1d1")),
+            ("b7a8q", None),
+            ("e4d5", None),
+            ("c3d5", None),
+            ("b7b8q", None),
+            ("e1f2", None),
+            ("h1h2", None),
+            ("h1h3", None),
+            ("b7b8n", None)
+        ];
+        for pair in ordered.windows(2) {
+            let (better, hash_move) = pair[0];
+            let (worse, _) = pair[1];
+            assert!(score(&ordering, better, hash_move) > score(&ordering, worse, None), "{} {}", better, worse);
+        }
+        //Killers belong to a ply, but history applies everywhere.
+        let other_ply = |mv: &str| ordering.score(&board, mv.parse().unwrap(), None, 2);
+        assert!(other_ply("e1f2") < other_ply("h1h2"));
+        assert!(other_ply("h1h2") > other_ply("h1h3"));
+    }
+
+    #[test]
+    fn history_stays_in_band() {
+        let board = Board::default();
+        let mut ordering = MoveOrdering::new();
+        let mv = "g1f3".parse().unwrap();
+        for _ in 0..1000 {
+            ordering.update(&board, mv, 0, MAX_PLY as u8);
+        }
+        ordering.new_search();
+        for _ in 0..1000 {
+            ordering.update(&board, mv, 0, MAX_PLY as u8);
+        }
+        //Another ply, where it isn't a killer.
+        assert!(ordering.score(&board, mv, None, 1) <= HISTORY_MAX);
+    }
+}
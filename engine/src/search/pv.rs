@@ -0,0 +1,88 @@
+use cozy_chess::*;
+
+use super::MAX_PLY;
+
+//Row `ply` holds the line from that ply, which is at most `MAX_PLY - ply` moves long,
+//so the rows are packed into a triangle instead of a square.
+const PV_TABLE_SIZE: usize = MAX_PLY * (MAX_PLY + 1) / 2;
+
+const fn row_start(ply: usize) -> usize {
+    ply * MAX_PLY - ply * (ply.saturating_sub(1)) / 2
+}
+
+/// The principal variation of every frame on the search stack.
+/// Each line is built from the line of the frame above it when a move raises alpha.
+pub struct PvTable {
+    moves: [u16; PV_TABLE_SIZE],
+    lens: [u8; MAX_PLY]
+}
+
+impl PvTable {
+    pub const fn new() -> Self {
+        Self {
+            moves: [0; PV_TABLE_SIZE],
+            lens: [0; MAX_PLY]
+        }
+    }
+
+    /// Forget the line at `ply`, for a frame that was just pushed.
+    pub fn clear(&mut self, ply: usize) {
+        self.lens[ply] = 0;
+    }
+
+    /// Make the line at `ply` start with `mv`, followed by the line at `ply + 1`.
+    pub fn update(&mut self, ply: usize, mv: Move) {
+        let child_len = self.lens.get(ply + 1).map_or(0, |&len| len as usize);
+        let start = row_start(ply);
+        let child_start = row_start(ply + 1);
+        self.moves[start] = mv.to_u16();
+        self.moves.copy_within(child_start..child_start + child_len, start + 1);
+        self.lens[ply] = child_len as u8 + 1;
+    }
+
+    /// The line at `ply`.
+    pub fn line(&self, ply: usize) -> impl Iterator<Item = Move> + '_ {
+        let start = row_start(ply);
+        self.moves[start..start + self.lens[ply] as usize]
+            .iter()
+            .map(|&mv| Move::from_u16(mv))
+    }
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_fit() {
+        for ply in 0..MAX_PLY {
+            assert_eq!(row_start(ply + 1) - row_start(ply), MAX_PLY - ply);
+        }
+        assert_eq!(row_start(MAX_PLY), PV_TABLE_SIZE);
+    }
+
+    #[test]
+    fn lines_extend_child_lines() {
+        let mv = |mv: &str| mv.parse::<Move>().unwrap();
+        let mut pv = PvTable::new();
+        pv.clear(2);
+        pv.update(2, mv("g1f3"));
+        pv.update(1, mv("e7e5"));
+        pv.update(0, mv("e2e4"));
+        assert!(pv.line(0).eq([mv("e2e4"), mv("e7e5"), mv("g1f3")]));
+        //A new best move at ply 1 with an empty child line.
+        pv.clear(2);
+        pv.update(1, mv("c7c5"));
+        assert!(pv.line(1).eq([mv("c7c5")]));
+        assert!(pv.line(0).eq([mv("e2e4"), mv("e7e5"), mv("g1f3")]));
+        //The deepest row has room for one move.
+        pv.update(MAX_PLY - 1, mv("a2a3"));
+        assert!(pv.line(MAX_PLY - 1).eq([mv("a2a3")]));
+    }
+}
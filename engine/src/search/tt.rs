@@ -0,0 +1,222 @@
+use cozy_chess::*;
+
+use super::{MATE_VALUE, MAX_PLY};
+
+/// The number of entries in the cart's table. Each entry takes 8 bytes,
+/// so this has to fit in the cart's 64 KiB of memory alongside everything else.
+pub const TT_SIZE: usize = 512;
+
+/// How a stored value relates to the true value of a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The value is exact.
+    Exact,
+    /// The search failed high, so the true value is at least this.
+    Lower,
+    /// The search failed low, so the true value is at most this.
+    Upper
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtEntry {
+    pub depth: u8,
+    pub bound: Bound,
+    pub value: i16,
+    /// The best move found, which may not be legal in the probed position
+    /// if another position shares its slot and check bits.
+    pub mv: Option<Move>
+}
+
+//Packed into 8 bytes. A zeroed slot is empty, so an empty table is all zeros.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    check: u16,
+    mv: u16,
+    value: i16,
+    depth: u8,
+    //The bound in the low 2 bits, with 0 meaning empty, and the age in the rest.
+    info: u8
+}
+
+impl Slot {
+    const EMPTY: Self = Self {
+        check: 0,
+        mv: 0,
+        value: 0,
+        depth: 0,
+        info: 0
+    };
+
+    fn bound(&self) -> Option<Bound> {
+        match self.info & 0b11 {
+            1 => Some(Bound::Exact),
+            2 => Some(Bound::Lower),
+            3 => Some(Bound::Upper),
+            _ => None
+        }
+    }
+
+    fn age(&self) -> u8 {
+        self.info >> 2
+    }
+}
+
+/// A fixed-size transposition table keyed by [`Board::hash`].
+/// It's meant to be statically allocated, so [`TranspositionTable::new`] is `const`
+/// and the size is a compile-time setting.
+pub struct TranspositionTable<const SIZE: usize = TT_SIZE> {
+    slots: [Slot; SIZE],
+    age: u8
+}
+
+//Mate values depend on the distance from the root, so they're stored
+//relative to the node instead and converted back when probed from another ply.
+fn value_to_tt(value: i16, ply: usize) -> i16 {
+    if value >= MATE_VALUE - MAX_PLY as i16 {
+        value + ply as i16
+    } else if value <= -MATE_VALUE + MAX_PLY as i16 {
+        value - ply as i16
+    } else {
+        value
+    }
+}
+
+fn value_from_tt(value: i16, ply: usize) -> i16 {
+    if value >= MATE_VALUE - MAX_PLY as i16 {
+        value - ply as i16
+    } else if value <= -MATE_VALUE + MAX_PLY as i16 {
+        value + ply as i16
+    } else {
+        value
+    }
+}
+
+impl<const SIZE: usize> TranspositionTable<SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::EMPTY; SIZE],
+            age: 0
+        }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.slots.fill(Slot::EMPTY);
+        self.age = 0;
+    }
+
+    /// Mark the start of a new search. Entries from older searches are replaced first.
+    pub fn new_search(&mut self) {
+        self.age = (self.age + 1) % 64;
+    }
+
+    fn index(hash: u64) -> (usize, u16) {
+        ((hash % SIZE as u64) as usize, (hash >> 48) as u16)
+    }
+
+    /// Look up a position. `ply` is the distance from the root, used to adjust mate values.
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TtEntry> {
+        let (index, check) = Self::index(hash);
+        let slot = &self.slots[index];
+        if slot.check != check {
+            return None;
+        }
+        Some(TtEntry {
+            depth: slot.depth,
+            bound: slot.bound()?,
+            value: value_from_tt(slot.value, ply),
+            mv: Move::try_from_u16(slot.mv)
+        })
+    }
+
+    /// Store a search result. An existing entry for another position is only replaced
+    /// if it's from an older search or wasn't searched deeper.
+    pub fn store(&mut self, hash: u64, ply: usize, entry: TtEntry) {
+        let (index, check) = Self::index(hash);
+        let slot = &mut self.slots[index];
+        let same_position = slot.check == check && slot.bound().is_some();
+        if !same_position && slot.bound().is_some() && slot.age() == self.age && slot.depth > entry.depth {
+            return;
+        }
+        let mv = match entry.mv {
+            Some(mv) => mv.to_u16(),
+            //Keep the old move for the same position rather than losing it.
+            None if same_position => slot.mv,
+            None => Move::NULL_ENCODING
+        };
+        let bound = match entry.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3
+        };
+        *slot = Slot {
+            check,
+            mv,
+            value: value_to_tt(entry.value, ply),
+            depth: entry.depth,
+            info: bound | self.age << 2
+        };
+    }
+}
+
+impl<const SIZE: usize> Default for TranspositionTable<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(depth: u8, value: i16, mv: Option<&str>) -> TtEntry {
+        TtEntry {
+            depth,
+            bound: Bound::Exact,
+            value,
+            mv: mv.map(|mv| mv.parse().unwrap())
+        }
+    }
+
+    #[test]
+    fn store_and_probe() {
+        let mut tt = TranspositionTable::<16>::new();
+        assert_eq!(tt.probe(0, 0), None);
+        let stored = entry(3, 42, Some("e2e4"));
+        tt.store(5, 0, stored);
+        assert_eq!(tt.probe(5, 0), Some(stored));
+        //Same slot, different check bits.
+        assert_eq!(tt.probe(5 | 1 << 48, 0), None);
+        //The move survives a store without one.
+        tt.store(5, 0, entry(4, 7, None));
+        assert_eq!(tt.probe(5, 0), Some(entry(4, 7, Some("e2e4"))));
+        tt.clear();
+        assert_eq!(tt.probe(5, 0), None);
+    }
+
+    #[test]
+    fn mate_values_are_relative_to_node() {
+        let mut tt = TranspositionTable::<16>::new();
+        //Mate in 3 plies from a node at ply 5 is mate in 3 plies from a node at ply 1.
+        tt.store(1, 5, entry(2, MATE_VALUE - 8, None));
+        assert_eq!(tt.probe(1, 1).unwrap().value, MATE_VALUE - 4);
+        tt.store(2, 5, entry(2, -MATE_VALUE + 8, None));
+        assert_eq!(tt.probe(2, 1).unwrap().value, -MATE_VALUE + 4);
+        tt.store(3, 5, entry(2, 300, None));
+        assert_eq!(tt.probe(3, 1).unwrap().value, 300);
+    }
+
+    #[test]
+    fn replacement_prefers_depth_then_age() {
+        let mut tt = TranspositionTable::<16>::new();
+        let other = 3 | 1 << 48;
+        tt.store(3, 0, entry(5, 1, None));
+        tt.store(other, 0, entry(2, 2, None));
+        assert_eq!(tt.probe(3, 0).unwrap().value, 1);
+        assert_eq!(tt.probe(other, 0), None);
+        tt.new_search();
+        tt.store(other, 0, entry(2, 2, None));
+        assert_eq!(tt.probe(3, 0), None);
+        assert_eq!(tt.probe(other, 0).unwrap().value, 2);
+    }
+}
@@ -1,17 +1,14 @@
-#![no_std]
+//Native builds link std so that workspace builds and lints cover the cart.
+#![cfg_attr(target_arch = "wasm32", no_std)]
 
 use cozy_chess::*;
+use glowfish_engine::*;
 
 mod wasm4;
-mod game;
 mod sprites;
 mod sounds;
-mod engine;
-mod rng;
 
 use wasm4::*;
-use game::*;
-use rng::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum GameMode {
@@ -26,6 +23,61 @@ impl GameMode {
     ];
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Difficulty {
+    Beginner,
+    Easy,
+    Medium,
+    Hard
+}
+
+//How the engine plays at a difficulty.
+struct EngineSettings {
+    nodes: u64,
+    eval_noise: i16,
+    use_book: bool,
+    //The chance out of 100 of playing a more or less random move.
+    blunder_chance: u64
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Beginner,
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard
+    ];
+
+    fn settings(&self) -> EngineSettings {
+        match self {
+            Difficulty::Beginner => EngineSettings {
+                nodes: 300,
+                eval_noise: 150,
+                use_book: false,
+                blunder_chance: 20
+            },
+            Difficulty::Easy => EngineSettings {
+                nodes: 2_000,
+                eval_noise: 60,
+                use_book: true,
+                blunder_chance: 8
+            },
+            Difficulty::Medium => EngineSettings {
+                nodes: 8_000,
+                eval_noise: 20,
+                use_book: true,
+                blunder_chance: 2
+            },
+            Difficulty::Hard => EngineSettings {
+                nodes: 20_000,
+                eval_noise: 0,
+                use_book: true,
+                blunder_chance: 0
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Promotion {
     Queen,
@@ -55,15 +107,35 @@ impl Promotion {
 enum MenuState {
     ModeSelect,
     ColorSelect,
+    DifficultySelect,
     PromotionSelect(Promotion),
     GameOver
 }
 
+//Far too large for the cart's stack, so it's never moved out of here.
+static mut SEARCH: Search = Search::new();
+static mut TT: TranspositionTable = TranspositionTable::new();
+
+//Keeps each frame's share of the search well within the frame budget.
+const ENGINE_NODES_PER_FRAME: u64 = 1000;
+//The minimum number of frames the engine takes to move.
+const ENGINE_MOVE_DELAY: u32 = 30;
+const BLUNDER_EVAL_NOISE: i16 = 1000;
+
 struct Glowfish {
     rng: Rng,
+    search: &'static mut Search,
+    tt: &'static mut TranspositionTable,
+    engine_move: Option<Move>,
+    //The search behind the engine's last move and the position it searched,
+    //if the move wasn't from the book.
+    engine_thoughts: Option<(Board, SearchResult)>,
+    //Toggled with X while no piece is selected.
+    show_thoughts: bool,
     menu: Option<MenuState>,
     game: ChessGame,
     mode: GameMode,
+    difficulty: Difficulty,
     engine_delay_timer: u32,
     p1_col: Color,
     selected_square: Square,
@@ -97,6 +169,62 @@ const WHITE: DrawColor = DrawColor::Four;
 
 const START_SQUARE: Square = Square::E2;
 
+const THOUGHTS_PV_LINES: usize = 3;
+const THOUGHTS_PV_MOVES_PER_LINE: usize = 2;
+
+//A line of text for the screen, built without pulling in the formatting machinery.
+#[derive(Clone, Copy)]
+struct Text {
+    bytes: [u8; (SCREEN_SIZE / CHAR_WIDTH) as usize],
+    len: usize
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self {
+            bytes: [0; (SCREEN_SIZE / CHAR_WIDTH) as usize],
+            len: 0
+        }
+    }
+}
+
+impl Text {
+    fn push(&mut self, byte: u8) {
+        if self.len < self.bytes.len() {
+            self.bytes[self.len] = byte;
+            self.len += 1;
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        for &byte in s.as_bytes() {
+            self.push(byte);
+        }
+    }
+
+    fn push_num(&mut self, n: u32) {
+        if n >= 10 {
+            self.push_num(n / 10);
+        }
+        self.push(b'0' + (n % 10) as u8);
+    }
+
+    fn push_move(&mut self, mv: Move) {
+        for square in [mv.from, mv.to] {
+            self.push(b'a' + square.file() as u8);
+            self.push(b'1' + square.rank() as u8);
+        }
+        if let Some(piece) = mv.promotion {
+            self.push(char::from(piece) as u8);
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        //Only ASCII is ever pushed.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
 impl Glowfish {
     fn perspective(&self) -> Color {
         match self.mode {
@@ -117,17 +245,47 @@ impl Glowfish {
     }
 
     fn update_game(&mut self, ctx: &mut Wasm4) {
+        let pad = self.active_player_gamepad(ctx);
+        self.move_cursor(ctx, pad);
         if self.mode == GameMode::VsEngine && self.p1_col != self.game.board().side_to_move() {
-            self.engine_delay_timer += 1;
-            if self.engine_delay_timer >= 30 {
-                let mv = engine::best_move(&self.game, self.rng.next());
-                self.try_play_move(ctx, mv);
-                self.engine_delay_timer = 0;
-            }
+            self.update_engine(ctx);
             return;
         }
 
-        let pad = self.active_player_gamepad(ctx);
+        if pad.button_x() {
+            if self.selected_piece.is_some() {
+                ctx.tone(sounds::PIECE_DESELECT);
+                self.selected_piece = None;
+            } else if self.mode == GameMode::VsEngine {
+                self.show_thoughts = !self.show_thoughts;
+            }
+        }
+
+        if pad.button_z() {
+            if let Some(selected_piece) = self.selected_piece {
+                let mv = Move {
+                    from: selected_piece,
+                    to: self.selected_square,
+                    promotion: None
+                };
+                // Castling by moving the king two squares
+                let mv = self.game.board().to_king_rook_castle(mv);
+                let pawns = self.game.board().pieces(Piece::Pawn);
+                let is_promotion = pawns.has(mv.from) && matches!(mv.to.rank(), Rank::First | Rank::Eighth);
+                if is_promotion {
+                    self.menu = Some(MenuState::PromotionSelect(Promotion::Queen));
+                } else {
+                    self.try_play_move(ctx, mv);
+                }
+            } else if self.game.board().colors(self.game.board().side_to_move()).has(self.selected_square) {
+                ctx.tone(sounds::PIECE_SELECT);
+                self.selected_piece = Some(self.selected_square);
+                self.show_thoughts = false;
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, ctx: &mut Wasm4, pad: GamepadState) {
         let mut x_offset = 0;
         let mut y_offset = 0;
         x_offset -= pad.left() as i8;
@@ -148,31 +306,56 @@ impl Glowfish {
         if init_square != self.selected_square {
             ctx.tone(sounds::SELECT_MOVE);
         }
+    }
 
-        if pad.button_x() {
-            ctx.tone(sounds::PIECE_DESELECT);
-            self.selected_piece = None;
-        }
-
-        if pad.button_z() {
-            if let Some(selected_piece) = self.selected_piece {
-                let mv = Move {
-                    from: selected_piece,
-                    to: self.selected_square,
-                    promotion: None
-                };
-                let pawns = self.game.board().pieces(Piece::Pawn);
-                let is_promotion = pawns.has(mv.from) && matches!(mv.to.rank(), Rank::First | Rank::Eighth);
-                if is_promotion {
-                    self.menu = Some(MenuState::PromotionSelect(Promotion::Queen));
+    //Runs a slice of the engine's search each frame so the cart stays responsive.
+    fn update_engine(&mut self, ctx: &mut Wasm4) {
+        if self.engine_delay_timer == 0 {
+            let settings = self.difficulty.settings();
+            let blunder = self.rng.next_u64() % 100 < settings.blunder_chance;
+            if settings.use_book && !blunder {
+                self.engine_move = book_move(&self.game, self.rng.next_u64());
+            }
+            self.engine_thoughts = None;
+            if self.engine_move.is_none() {
+                let limits = if blunder {
+                    //A shallow search with overwhelming noise plays a more or less random move.
+                    SearchLimits {
+                        max_depth: 1,
+                        eval_noise: BLUNDER_EVAL_NOISE,
+                        ..DEFAULT_LIMITS
+                    }
                 } else {
-                    self.try_play_move(ctx, mv);
-                }
-            } else if self.game.board().colors(self.game.board().side_to_move()).has(self.selected_square) {
-                ctx.tone(sounds::PIECE_SELECT);
-                self.selected_piece = Some(self.selected_square);
+                    SearchLimits {
+                        max_nodes: Some(settings.nodes),
+                        eval_noise: settings.eval_noise,
+                        ..DEFAULT_LIMITS
+                    }
+                };
+                self.search.start(&self.game, SearchLimits {
+                    noise_seed: self.rng.next_u64(),
+                    ..limits
+                });
             }
         }
+        self.engine_delay_timer += 1;
+        if self.engine_move.is_none() && self.search.step(self.tt, ENGINE_NODES_PER_FRAME, &mut ()) {
+            let result = self.search.result();
+            self.engine_move = result.as_ref().map(|result| result.mv);
+            self.engine_thoughts = result.map(|result| (self.game.board().clone(), result));
+        }
+        if self.engine_delay_timer >= ENGINE_MOVE_DELAY {
+            if let Some(mv) = self.engine_move.take() {
+                self.try_play_move(ctx, mv);
+                self.engine_delay_timer = 0;
+            }
+        }
+    }
+
+    fn reset_engine(&mut self) {
+        self.engine_move = None;
+        self.engine_thoughts = None;
+        self.engine_delay_timer = 0;
     }
 
     fn try_play_move(&mut self, ctx: &mut Wasm4, mv: Move) {
@@ -216,19 +399,19 @@ impl Glowfish {
             };
             self.game.board().generate_moves_for(square.bitboard(), |moves| {
                 for mv in moves {
-                    let (fill, outline) = if self.game.board().color_on(mv.to) == Some(piece_color) {
+                    let is_castle = self.game.board().color_on(mv.to) == Some(piece_color);
+                    let (fill, outline) = if is_castle {
                         (main_col, inverted_col)
                     } else {
                         (inverted_col, main_col)
                     };
-                    let (x, y) = self.square_coords(mv.to);
-                    let offset = (SQUARE_SIZE - MOVE_INDICATOR_SIZE) as i32 / 2;
-                    ctx.draw_2bpp_sprite(
-                        sprites::MOVE_INDICATOR,
-                        offset + x,
-                        offset + y,
-                        [DrawColor::None, fill, outline, DrawColor::None]
-                    );
+                    self.draw_move_indicator(ctx, mv.to, fill, outline);
+                    if is_castle {
+                        let standard = self.game.board().to_standard_castle(mv);
+                        if standard != mv {
+                            self.draw_move_indicator(ctx, standard.to, inverted_col, main_col);
+                        }
+                    }
                 }
                 false
             });
@@ -256,6 +439,19 @@ impl Glowfish {
                     self.p1_col as usize
                 );
             }
+            Some(MenuState::DifficultySelect) => {
+                self.draw_menu(
+                    ctx,
+                    &["Difficulty"],
+                    &[
+                        "Beginner",
+                        "Easy",
+                        "Medium",
+                        "Hard"
+                    ],
+                    self.difficulty as usize
+                );
+            }
             Some(MenuState::PromotionSelect(current_promotion)) => {
                 let (piece_color, inverted_color) = match self.game.board().side_to_move() {
                     Color::White => (WHITE, BLACK),
@@ -306,8 +502,44 @@ impl Glowfish {
                 };
                 self.draw_menu(ctx, &[title], &["New game"], 0);
             }
-            None => {}
+            None => {
+                if let (GameMode::VsEngine, true, Some((board, thoughts))) = (self.mode, self.show_thoughts, &self.engine_thoughts) {
+                    self.draw_thoughts(ctx, board, thoughts);
+                }
+            }
+        }
+    }
+
+    //Shows what the engine expected when it made its last move.
+    fn draw_thoughts(&self, ctx: &mut Wasm4, board: &Board, thoughts: &SearchResult) {
+        let mut lines = [Text::default(); 2 + THOUGHTS_PV_LINES];
+        lines[0].push_str("Depth ");
+        lines[0].push_num(thoughts.depth as u32);
+        let mate_plies = MATE_VALUE - thoughts.value.abs();
+        if mate_plies <= MAX_PLY as i16 {
+            lines[1].push_str(if thoughts.value > 0 { "Mate in " } else { "Mated in " });
+            lines[1].push_num((mate_plies as u32).div_ceil(2));
+        } else {
+            let centipawns = thoughts.value.unsigned_abs() as u32;
+            lines[1].push_str(if thoughts.value < 0 { "Score -" } else { "Score +" });
+            lines[1].push_num(centipawns / 100);
+            lines[1].push(b'.');
+            lines[1].push(b'0' + (centipawns / 10 % 10) as u8);
+            lines[1].push(b'0' + (centipawns % 10) as u8);
+        }
+        let mut board = board.clone();
+        let pv = thoughts.pv.iter().take(THOUGHTS_PV_LINES * THOUGHTS_PV_MOVES_PER_LINE);
+        for (i, &mv) in pv.enumerate() {
+            let line = &mut lines[2 + i / THOUGHTS_PV_MOVES_PER_LINE];
+            if i % THOUGHTS_PV_MOVES_PER_LINE != 0 {
+                line.push(b' ');
+            }
+            line.push_move(board.to_standard_castle(mv));
+            board.play_unchecked(mv);
         }
+        let line_count = 2 + thoughts.pv.len().div_ceil(THOUGHTS_PV_MOVES_PER_LINE).min(THOUGHTS_PV_LINES);
+        let lines = lines.each_ref().map(|line| line.as_str());
+        self.draw_menu(ctx, &["CPU thinking"], &lines[..line_count], usize::MAX);
     }
 
     fn draw_menu(&self, ctx: &mut Wasm4, title: &[&str], options: &[&str], selected: usize) {
@@ -399,6 +631,17 @@ impl Glowfish {
         (BOARD_X as i32 + x, BOARD_Y as i32 + y)
     }
 
+    fn draw_move_indicator(&self, ctx: &mut Wasm4, square: Square, fill: DrawColor, outline: DrawColor) {
+        let (x, y) = self.square_coords(square);
+        let offset = (SQUARE_SIZE - MOVE_INDICATOR_SIZE) as i32 / 2;
+        ctx.draw_2bpp_sprite(
+            sprites::MOVE_INDICATOR,
+            offset + x,
+            offset + y,
+            [DrawColor::None, fill, outline, DrawColor::None]
+        );
+    }
+
     fn highlight_square(&self, ctx: &mut Wasm4, square: Square) {
         let outline = match self.game.board().color_on(square) {
             Some(Color::White) | None => BLACK,
@@ -414,8 +657,14 @@ impl Runtime for Glowfish {
         ctx.set_palette([0xDA5630, 0xA22200, 0x000000, 0xFFFFFF]);
         Self {
             rng: Rng::new(),
+            search: unsafe { &mut *core::ptr::addr_of_mut!(SEARCH) },
+            tt: unsafe { &mut *core::ptr::addr_of_mut!(TT) },
+            engine_move: None,
+            engine_thoughts: None,
+            show_thoughts: false,
             menu: Some(MenuState::ModeSelect),
             mode: GameMode::VsPlayer,
+            difficulty: Difficulty::Medium,
             p1_col: Color::White,
             engine_delay_timer: 0,
             game: ChessGame::new(),
@@ -426,7 +675,7 @@ impl Runtime for Glowfish {
     }
 
     fn update(&mut self, ctx: &mut Wasm4) {
-        self.rng.next();
+        self.rng.next_u64();
         let menu_pad = self.main_player_gamepad(ctx);
         let player_pad = self.active_player_gamepad(ctx);
         match &mut self.menu {
@@ -458,6 +707,27 @@ impl Runtime for Glowfish {
                 if menu_pad.button_x() {
                     self.menu = Some(MenuState::ModeSelect);
                     ctx.tone(sounds::SELECT_MOVE);
+                } else if menu_pad.button_z() {
+                    self.menu = match self.mode {
+                        GameMode::VsPlayer => None,
+                        GameMode::VsEngine => Some(MenuState::DifficultySelect)
+                    };
+                    ctx.tone(sounds::SELECT_MOVE);
+                }
+            }
+            Some(MenuState::DifficultySelect) => {
+                let mut shift = 0;
+                shift -= menu_pad.up() as usize;
+                shift += menu_pad.down() as usize;
+                if shift != 0 {
+                    let index = (self.difficulty as usize + shift)
+                        .rem_euclid(Difficulty::ALL.len());
+                    self.difficulty = Difficulty::ALL[index];
+                    ctx.tone(sounds::SELECT_MOVE);
+                }
+                if menu_pad.button_x() {
+                    self.menu = Some(MenuState::ColorSelect);
+                    ctx.tone(sounds::SELECT_MOVE);
                 } else if menu_pad.button_z() {
                     self.menu = None;
                     ctx.tone(sounds::SELECT_MOVE);
@@ -495,8 +765,10 @@ impl Runtime for Glowfish {
                 if menu_pad.button_z() {
                     self.menu = Some(MenuState::ModeSelect);
                     self.game = ChessGame::new();
+                    self.reset_engine();
                     self.selected_square = START_SQUARE;
                     self.selected_piece = None;
+                    self.show_thoughts = false;
                 }
             }
             None => self.update_game(ctx)
@@ -138,6 +138,7 @@ macro_rules! __wasm4_main {
     ($runtime:ty) => {
         static mut RUNTIME: Option<$runtime> = None;
 
+        #[cfg(target_arch = "wasm32")]
         #[panic_handler]
         fn panic(info: &core::panic::PanicInfo) -> ! {
             core::arch::wasm32::unreachable()
@@ -0,0 +1,221 @@
+//! # `cozy-chess-pgn`
+
+//! ## PGN reading and writing for `cozy-chess`
+
+//! Parses [PGN](https://www.chessprogramming.org/Portable_Game_Notation) game collections
+//! into trees of [`Move`]s validated against a [`Board`], and writes them back out.
+//! Supports tag pairs, comments, NAGs, recursive variations, `FEN`/`SetUp` tags and
+//! Chess960 games through the `Variant` tag.
+//! See [`Game`] and [`GameReader`] to get started.
+
+use cozy_chess::*;
+
+mod read;
+mod write;
+
+pub use read::*;
+
+/// The result of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Drawn,
+    Unknown
+}
+
+impl GameResult {
+    /// Get the PGN result token.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess_pgn::*;
+    /// assert_eq!(GameResult::Drawn.as_str(), "1/2-1/2");
+    /// assert_eq!(GameResult::Unknown.as_str(), "*");
+    /// ```
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Drawn => "1/2-1/2",
+            GameResult::Unknown => "*"
+        }
+    }
+
+    /// Parse a PGN result token.
+    pub fn from_token(token: &str) -> Option<Self> {
+        Some(match token {
+            "1-0" => GameResult::WhiteWins,
+            "0-1" => GameResult::BlackWins,
+            "1/2-1/2" => GameResult::Drawn,
+            "*" => GameResult::Unknown,
+            _ => return None
+        })
+    }
+}
+
+/// A move in a game tree, along with its annotations and alternatives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveNode {
+    /// The move played. Castling is encoded as king captures rook.
+    pub mv: Move,
+    /// [Numeric Annotation Glyphs](https://en.wikipedia.org/wiki/Numeric_Annotation_Glyphs) for the move.
+    pub nags: Vec<u8>,
+    /// Comments following the move.
+    pub comments: Vec<String>,
+    /// Alternative lines played instead of this move.
+    pub variations: Vec<Line>
+}
+
+impl MoveNode {
+    /// Create a node with no annotations or variations.
+    pub fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new()
+        }
+    }
+}
+
+/// A sequence of moves. This is either the mainline or a variation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Line {
+    /// Comments preceding the first move.
+    pub comments: Vec<String>,
+    /// The moves in the line.
+    pub moves: Vec<MoveNode>
+}
+
+/// A single game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    /// The tag pairs in order of appearance.
+    pub tags: Vec<(String, String)>,
+    /// The starting position.
+    pub board: Board,
+    /// Whether the game is a Chess960 game.
+    pub chess960: bool,
+    /// The mainline and its variations.
+    pub mainline: Line,
+    /// The game termination marker.
+    pub result: GameResult
+}
+
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*")
+];
+
+impl Game {
+    /// Create a game with no moves starting from some position.
+    /// The Seven Tag Roster is filled with unknown values, and the
+    /// `SetUp`, `FEN` and `Variant` tags are set if required.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// # use cozy_chess_pgn::*;
+    /// let game = Game::new(Board::default(), false);
+    /// assert_eq!(game.tag("Date"), Some("????.??.??"));
+    /// assert_eq!(game.tag("FEN"), None);
+    /// ```
+    pub fn new(board: Board, chess960: bool) -> Self {
+        let mut game = Self {
+            tags: Vec::new(),
+            board,
+            chess960,
+            mainline: Line::default(),
+            result: GameResult::Unknown
+        };
+        for (name, value) in SEVEN_TAG_ROSTER {
+            game.set_tag(name, value);
+        }
+        if chess960 {
+            game.set_tag("Variant", "Chess960");
+        }
+        if chess960 || game.board != Board::default() {
+            let fen = if chess960 {
+                format!("{:#}", game.board)
+            } else {
+                format!("{}", game.board)
+            };
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game
+    }
+
+    /// Get the value of a tag.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set the value of a tag, replacing it if it already exists.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        if let Some((_, v)) = self.tags.iter_mut().find(|(n, _)| n == name) {
+            *v = value.to_owned();
+        } else {
+            self.tags.push((name.to_owned(), value.to_owned()));
+        }
+    }
+
+    /// Get the position at the end of the mainline.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// # use cozy_chess_pgn::*;
+    /// let game: Game = "1. e4 e5 2. Nf3 *".parse().unwrap();
+    /// let expected: Board = "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    ///     .parse().unwrap();
+    /// assert_eq!(game.final_board(), expected);
+    /// ```
+    pub fn final_board(&self) -> Board {
+        let mut board = self.board.clone();
+        for node in &self.mainline.moves {
+            board.play_unchecked(node.mv);
+        }
+        board
+    }
+
+    /// Iterate the positions and moves of the mainline.
+    /// Each item is a position along with the move played from it.
+    pub fn mainline_positions(&self) -> impl Iterator<Item = (Board, Move)> + '_ {
+        let mut board = self.board.clone();
+        self.mainline.moves.iter().map(move |node| {
+            let position = board.clone();
+            board.play_unchecked(node.mv);
+            (position, node.mv)
+        })
+    }
+
+    /// Append a move to the mainline, checking its legality.
+    /// Returns `false` if the move was illegal.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess::*;
+    /// # use cozy_chess_pgn::*;
+    /// let mut game = Game::new(Board::default(), false);
+    /// assert!(game.push("e2e4".parse().unwrap()));
+    /// assert!(!game.push("e2e4".parse().unwrap()));
+    /// game.result = GameResult::Unknown;
+    /// assert!(format!("{}", game).ends_with("1. e4 *\n"));
+    /// ```
+    pub fn push(&mut self, mv: Move) -> bool {
+        let mut board = self.final_board();
+        if !board.try_play(mv).unwrap_or(false) {
+            return false;
+        }
+        self.mainline.moves.push(MoveNode::new(mv));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
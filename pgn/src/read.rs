@@ -0,0 +1,372 @@
+use std::str::FromStr;
+
+use cozy_chess::*;
+
+use crate::*;
+
+/// The kind of error that occurred while reading PGN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnErrorKind {
+    InvalidTag,
+    UnterminatedComment,
+    UnterminatedVariation,
+    UnexpectedToken,
+    InvalidFen(FenParseError),
+    InvalidMove(SanParseError),
+    MissingMove,
+    MissingGame
+}
+
+/// An error while reading PGN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PgnError {
+    /// The kind of error.
+    pub kind: PgnErrorKind,
+    /// The line the error occurred on, starting from 1.
+    pub line: usize
+}
+
+impl std::fmt::Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} on line {}", self.kind, self.line)
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token<'s> {
+    Tag(&'s str, String),
+    Comment(&'s str),
+    Nag(u8),
+    StartVariation,
+    EndVariation,
+    Result(GameResult),
+    MoveNumber,
+    San(&'s str)
+}
+
+struct Lexer<'s> {
+    src: &'s str,
+    pos: usize,
+    line: usize
+}
+
+impl<'s> Lexer<'s> {
+    fn error(&self, kind: PgnErrorKind) -> PgnError {
+        PgnError {
+            kind,
+            line: self.line
+        }
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn take_while(&mut self, mut predicate: impl FnMut(char) -> bool) -> &'s str {
+        let start = self.pos;
+        while self.peek_char().is_some_and(&mut predicate) {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let at_line_start = self.pos == 0 || self.src[..self.pos].ends_with('\n');
+            match self.peek_char() {
+                //Escape mechanism; the rest of the line is ignored.
+                Some('%') if at_line_start => {
+                    self.take_while(|c| c != '\n');
+                }
+                Some(c) if c.is_whitespace() || c == '.' => {
+                    self.bump();
+                }
+                _ => break
+            }
+        }
+    }
+
+    fn tag(&mut self) -> Result<Token<'s>, PgnError> {
+        self.take_while(char::is_whitespace);
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        self.take_while(char::is_whitespace);
+        if name.is_empty() || self.bump() != Some('"') {
+            return Err(self.error(PgnErrorKind::InvalidTag));
+        }
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\\') => match self.bump() {
+                    Some(c) => value.push(c),
+                    None => return Err(self.error(PgnErrorKind::InvalidTag))
+                },
+                Some('"') => break,
+                Some('\n') | None => return Err(self.error(PgnErrorKind::InvalidTag)),
+                Some(c) => value.push(c)
+            }
+        }
+        self.take_while(char::is_whitespace);
+        if self.bump() != Some(']') {
+            return Err(self.error(PgnErrorKind::InvalidTag));
+        }
+        Ok(Token::Tag(name, value))
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'s>>, PgnError> {
+        self.skip_whitespace();
+        let c = match self.peek_char() {
+            Some(c) => c,
+            None => return Ok(None)
+        };
+        let is_symbol = |c: char| c.is_ascii_alphanumeric() || "_+#=:-/".contains(c);
+        let token = match c {
+            '[' => {
+                self.bump();
+                self.tag()?
+            }
+            '{' => {
+                self.bump();
+                let comment = self.take_while(|c| c != '}');
+                if self.bump().is_none() {
+                    return Err(self.error(PgnErrorKind::UnterminatedComment));
+                }
+                Token::Comment(comment.trim())
+            }
+            ';' => {
+                self.bump();
+                Token::Comment(self.take_while(|c| c != '\n').trim())
+            }
+            '(' => {
+                self.bump();
+                Token::StartVariation
+            }
+            ')' => {
+                self.bump();
+                Token::EndVariation
+            }
+            '*' => {
+                self.bump();
+                Token::Result(GameResult::Unknown)
+            }
+            '$' => {
+                self.bump();
+                let nag = self.take_while(|c| c.is_ascii_digit());
+                Token::Nag(nag.parse().map_err(|_| self.error(PgnErrorKind::UnexpectedToken))?)
+            }
+            '!' | '?' => {
+                let nag = match self.take_while(|c| c == '!' || c == '?') {
+                    "!" => 1,
+                    "?" => 2,
+                    "!!" => 3,
+                    "??" => 4,
+                    "!?" => 5,
+                    "?!" => 6,
+                    _ => return Err(self.error(PgnErrorKind::UnexpectedToken))
+                };
+                Token::Nag(nag)
+            }
+            c if is_symbol(c) => {
+                let symbol = self.take_while(is_symbol);
+                if let Some(result) = GameResult::from_token(symbol) {
+                    Token::Result(result)
+                } else if symbol.chars().all(|c| c.is_ascii_digit()) {
+                    Token::MoveNumber
+                } else {
+                    Token::San(symbol)
+                }
+            }
+            _ => return Err(self.error(PgnErrorKind::UnexpectedToken))
+        };
+        Ok(Some(token))
+    }
+}
+
+/// An iterator over the games in a PGN string.
+/// # Examples
+/// ```
+/// # use cozy_chess_pgn::*;
+/// const PGN: &str = r#"
+/// [Event "First"]
+///
+/// 1. e4 e5 2. Nf3 Nc6 1-0
+///
+/// [Event "Second"]
+///
+/// 1. d4 d5 (1... Nf6 2. c4) 2. c4 {Queen's Gambit} 0-1
+/// "#;
+/// let games = GameReader::new(PGN).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[0].tag("Event"), Some("First"));
+/// assert_eq!(games[0].result, GameResult::WhiteWins);
+/// assert_eq!(games[1].mainline.moves.len(), 3);
+/// assert_eq!(games[1].mainline.moves[1].variations[0].moves.len(), 2);
+/// assert_eq!(games[1].mainline.moves[2].comments, ["Queen's Gambit"]);
+/// ```
+pub struct GameReader<'s> {
+    lexer: Lexer<'s>,
+    peeked: Option<Token<'s>>,
+    failed: bool
+}
+
+impl<'s> GameReader<'s> {
+    /// Create a reader over a PGN string that may contain multiple games.
+    pub fn new(pgn: &'s str) -> Self {
+        Self {
+            lexer: Lexer {
+                src: pgn,
+                pos: 0,
+                line: 1
+            },
+            peeked: None,
+            failed: false
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token<'s>>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next_token()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'s>>, PgnError> {
+        self.peek()?;
+        Ok(self.peeked.take())
+    }
+
+    fn error(&self, kind: PgnErrorKind) -> PgnError {
+        self.lexer.error(kind)
+    }
+
+    fn read_game(&mut self) -> Result<Option<Game>, PgnError> {
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(..)) = self.peek()? {
+            if let Some(Token::Tag(name, value)) = self.next_token()? {
+                tags.push((name.to_owned(), value));
+            }
+        }
+        if tags.is_empty() && self.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let variant = tags.iter()
+            .find(|(name, _)| name == "Variant")
+            .map(|(_, value)| value.to_ascii_lowercase());
+        let chess960 = matches!(
+            variant.as_deref(),
+            Some("chess960" | "chess 960" | "fischerandom" | "fischer random")
+        );
+        let fen = tags.iter()
+            .find(|(name, _)| name == "FEN")
+            .map(|(_, value)| value.as_str());
+        let board = match fen {
+            Some(fen) if chess960 => Board::from_fen(fen, true)
+                .or_else(|_| Board::from_fen(fen, false)),
+            Some(fen) => Board::from_fen(fen, false),
+            None => Ok(Board::default())
+        }.map_err(|e| self.error(PgnErrorKind::InvalidFen(e)))?;
+
+        let mainline = self.read_line(&board, false)?;
+        let result = match self.peek()? {
+            Some(&Token::Result(result)) => {
+                self.next_token()?;
+                result
+            }
+            //Missing termination marker; fall back to the tag.
+            _ => tags.iter()
+                .find(|(name, _)| name == "Result")
+                .and_then(|(_, value)| GameResult::from_token(value))
+                .unwrap_or(GameResult::Unknown)
+        };
+        Ok(Some(Game {
+            tags,
+            board,
+            chess960,
+            mainline,
+            result
+        }))
+    }
+
+    fn read_line(&mut self, board: &Board, in_variation: bool) -> Result<Line, PgnError> {
+        let mut line = Line::default();
+        let mut board = board.clone();
+        let mut prev_board = None;
+        loop {
+            match self.peek()? {
+                Some(Token::Result(_)) | Some(Token::Tag(..)) | None if !in_variation => break,
+                None => return Err(self.error(PgnErrorKind::UnterminatedVariation)),
+                _ => {}
+            }
+            match self.next_token()?.unwrap() {
+                Token::Comment(comment) => {
+                    let comments = match line.moves.last_mut() {
+                        Some(node) => &mut node.comments,
+                        None => &mut line.comments
+                    };
+                    comments.push(comment.to_owned());
+                }
+                Token::Nag(nag) => {
+                    let node = line.moves.last_mut()
+                        .ok_or_else(|| self.error(PgnErrorKind::MissingMove))?;
+                    node.nags.push(nag);
+                }
+                Token::MoveNumber => {}
+                Token::San(san) => {
+                    let mv = board.parse_san(san)
+                        .map_err(|e| self.error(PgnErrorKind::InvalidMove(e)))?;
+                    let mut child = board.clone();
+                    if !child.try_play(mv).unwrap_or(false) {
+                        return Err(self.error(PgnErrorKind::InvalidMove(SanParseError::IllegalMove)));
+                    }
+                    prev_board = Some(core::mem::replace(&mut board, child));
+                    line.moves.push(MoveNode::new(mv));
+                }
+                Token::StartVariation => {
+                    let prev_board = prev_board.as_ref()
+                        .ok_or_else(|| self.error(PgnErrorKind::MissingMove))?;
+                    let variation = self.read_line(prev_board, true)?;
+                    line.moves.last_mut().unwrap().variations.push(variation);
+                }
+                Token::EndVariation if in_variation => break,
+                _ => return Err(self.error(PgnErrorKind::UnexpectedToken))
+            }
+        }
+        Ok(line)
+    }
+}
+
+impl Iterator for GameReader<'_> {
+    type Item = Result<Game, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let game = self.read_game().transpose();
+        if let Some(Err(_)) = game {
+            self.failed = true;
+        }
+        game
+    }
+}
+
+impl FromStr for Game {
+    type Err = PgnError;
+
+    /// Parse a single game. Any games after the first are ignored.
+    fn from_str(pgn: &str) -> Result<Self, Self::Err> {
+        let mut reader = GameReader::new(pgn);
+        reader.next().unwrap_or_else(|| Err(reader.error(PgnErrorKind::MissingGame)))
+    }
+}
@@ -0,0 +1,109 @@
+use super::*;
+
+const GAMES: &str = r#"
+[Event "Casual game"]
+[Site "?"]
+[Date "2022.01.01"]
+[Round "?"]
+[White "White \"The Great\""]
+[Black "Black"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6?! (3... Bc5 4. c3 (4. O-O Nf6
+5. d3) 4... Nf6 $1) 4. Ng5 d5 5. exd5 Na5 ; Line comment
+6. Bb5+ c6 7. dxc6 bxc6 8. Be2 h6 9. Nf3 e4 10. Ne5 Bd6 11. f4 exf3 12. Nxf3
+O-O 13. O-O Qc7 14. d4 c5 15. dxc5 Bxc5+ 16. Kh1 Rd8 17. Qe1 Bd6 18. h3 Bb7
+19. Nc3 Rac8 20. Bd3 Nc4 21. Bxc4 Qxc4 22. Qh4 Bxf3 23. Rxf3 Be5 24. Bxh6
+Bxc3 25. bxc3 gxh6 26. Qxf6 Qc5 27. Rg3+ 1-0
+
+[Event "Set up"]
+[SetUp "1"]
+[FEN "4k3/P7/8/8/8/8/8/4K3 w - - 0 1"]
+[Result "*"]
+
+1. a8=Q+ Kd7 2. Qb7+ *
+
+[Event "Chess960"]
+[Variant "Chess960"]
+[SetUp "1"]
+[FEN "rq1kr3/p1ppbp1p/bpn3pB/3Np3/3P4/1P1Q1Nn1/P1P1BPPP/R2KR3 w AEae - 3 15"]
+[Result "*"]
+
+15. O-O-O *
+
+% Escaped line
+1. d4 d5 2. c4 dxc4 *
+"#;
+
+fn read_all(pgn: &str) -> Vec<Game> {
+    GameReader::new(pgn).collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn reads_games() {
+    let games = read_all(GAMES);
+    assert_eq!(games.len(), 4);
+
+    let game = &games[0];
+    assert_eq!(game.tag("White"), Some("White \"The Great\""));
+    assert_eq!(game.result, GameResult::WhiteWins);
+    assert_eq!(game.mainline.comments, ["Opening comment"]);
+    assert_eq!(game.mainline.moves.len(), 53);
+    let nf6 = &game.mainline.moves[5];
+    assert_eq!(nf6.nags, [6]);
+    assert_eq!(nf6.variations.len(), 1);
+    let variation = &nf6.variations[0];
+    assert_eq!(variation.moves[0].mv, "f8c5".parse().unwrap());
+    assert_eq!(variation.moves[1].variations[0].moves.len(), 3);
+    assert_eq!(variation.moves[2].nags, [1]);
+    assert_eq!(game.mainline.moves[9].comments, ["Line comment"]);
+
+    let game = &games[1];
+    assert_eq!(game.board, "4k3/P7/8/8/8/8/8/4K3 w - - 0 1".parse().unwrap());
+    assert_eq!(game.mainline.moves[0].mv, "a7a8q".parse().unwrap());
+
+    let game = &games[2];
+    assert!(game.chess960);
+    assert_eq!(game.mainline.moves[0].mv, "d1a1".parse().unwrap());
+
+    let game = &games[3];
+    assert!(game.tags.is_empty());
+    assert_eq!(game.final_board().pieces(Piece::Pawn).popcnt(), 15);
+}
+
+#[test]
+fn round_trip() {
+    let games = read_all(GAMES);
+    let written = games.iter()
+        .map(|game| format!("{}\n", game))
+        .collect::<String>();
+    for line in written.lines() {
+        assert!(line.len() <= 79, "{}", line);
+    }
+    assert_eq!(read_all(&written), games);
+}
+
+#[test]
+fn closing_braces_are_left_out_of_comments() {
+    let mut game: Game = "{Before} 1. e4 e5 (1... c5) *".parse().unwrap();
+    game.mainline.comments[0] = "Not {really} a brace comment".to_owned();
+    game.mainline.moves[1].comments.push("First line }\nSecond line".to_owned());
+    let read: Game = format!("{}", game).parse().unwrap();
+    assert_eq!(read.mainline.comments, ["Not {really a brace comment"]);
+    assert_eq!(read.mainline.moves[0].variations, game.mainline.moves[0].variations);
+    assert_eq!(read.mainline.moves[1].comments, ["First line \nSecond line"]);
+}
+
+#[test]
+fn rejects_bad_input() {
+    let error = |pgn: &str| pgn.parse::<Game>().unwrap_err();
+    assert_eq!(error("1. e4 e5 2. Ke3 *").kind, PgnErrorKind::InvalidMove(SanParseError::IllegalMove));
+    assert_eq!(error("1. e4 e5 2. Zz9 *").kind, PgnErrorKind::InvalidMove(SanParseError::InvalidSan));
+    assert_eq!(error("1. e4 (1. d4 *").kind, PgnErrorKind::UnexpectedToken);
+    assert_eq!(error("1. e4 (1. d4").kind, PgnErrorKind::UnterminatedVariation);
+    assert_eq!(error("1. e4 {comment").kind, PgnErrorKind::UnterminatedComment);
+    assert_eq!(error("(1. e4) *").kind, PgnErrorKind::MissingMove);
+    assert_eq!(error("[Event \"?\"\n1. e4 *").kind, PgnErrorKind::InvalidTag);
+    assert_eq!(error("\n\n1. e4 e5\n2. Nf3 Nf3 *").line, 4);
+    assert!(matches!(error("[FEN \"8/8/8 w - - 0 1\"]\n*").kind, PgnErrorKind::InvalidFen(_)));
+}
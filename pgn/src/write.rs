@@ -0,0 +1,98 @@
+use std::fmt::{Display, Formatter};
+
+use cozy_chess::*;
+
+use crate::*;
+
+const MAX_LINE_LEN: usize = 79;
+
+//A comment ends at the first `}` and PGN has no way to escape one, so they're left out.
+fn comment_token(comment: &str) -> String {
+    format!("{{{}}}", comment.replace('}', ""))
+}
+
+fn line_tokens(tokens: &mut Vec<String>, board: &Board, line: &Line) {
+    for comment in &line.comments {
+        tokens.push(comment_token(comment));
+    }
+    let mut board = board.clone();
+    let mut needs_number = true;
+    for node in &line.moves {
+        let number = board.fullmove_number();
+        if board.side_to_move() == Color::White {
+            tokens.push(format!("{}.", number));
+        } else if needs_number {
+            tokens.push(format!("{}...", number));
+        }
+        needs_number = false;
+        let san = board.san(node.mv).expect("Illegal move in game tree!");
+        tokens.push(format!("{}", san));
+        for nag in &node.nags {
+            tokens.push(format!("${}", nag));
+        }
+        for comment in &node.comments {
+            tokens.push(comment_token(comment));
+            needs_number = true;
+        }
+        for variation in &node.variations {
+            tokens.push("(".to_owned());
+            line_tokens(tokens, &board, variation);
+            tokens.push(")".to_owned());
+            needs_number = true;
+        }
+        board.play_unchecked(node.mv);
+    }
+}
+
+impl Display for Game {
+    /// Write the game as PGN. The `Result` tag is kept in sync with [`Game::result`].
+    /// Any `}` in a comment is left out, since it would end the comment.
+    /// # Panics
+    /// This may panic if the game tree contains illegal moves.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess_pgn::*;
+    /// const PGN: &str = "[Event \"?\"]\n\n1. e4 $1 {Best by test} (1. d4 d5) 1... e5 2. Nf3 *\n";
+    /// let game: Game = PGN.parse().unwrap();
+    /// assert_eq!(format!("{}", game), PGN);
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in &self.tags {
+            let value = if name == "Result" {
+                self.result.as_str()
+            } else {
+                value
+            };
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut tokens = Vec::new();
+        line_tokens(&mut tokens, &self.board, &self.mainline);
+        tokens.push(self.result.as_str().to_owned());
+
+        let mut line_len = 0;
+        let mut prev: Option<&str> = None;
+        for token in &tokens {
+            let separated = match prev {
+                Some(prev) => prev != "(" && token != ")",
+                None => false
+            };
+            let len = token.len() + separated as usize;
+            if separated && line_len + len > MAX_LINE_LEN {
+                writeln!(f)?;
+                line_len = 0;
+            } else if separated {
+                write!(f, " ")?;
+                line_len += 1;
+            }
+            write!(f, "{}", token)?;
+            line_len += token.len();
+            prev = Some(token);
+        }
+        writeln!(f)
+    }
+}
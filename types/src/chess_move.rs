@@ -13,6 +13,79 @@ pub struct Move {
     pub promotion: Option<Piece>
 }
 
+impl Move {
+    /// The 16-bit encoding reserved for the absence of a move, also known as the null move.
+    /// No [`Move`] encodes to this value. See [`Move::to_u16`].
+    pub const NULL_ENCODING: u16 = u16::MAX;
+
+    /// Pack a move into 16 bits.
+    /// The `from` square is stored in bits 0-5 and the `to` square in bits 6-11.
+    /// Bits 12-15 store the promotion piece, with `0` for no promotion
+    /// and the piece's index plus one otherwise.
+    /// This encoding is stable and can be used in persistent formats.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess_types::*;
+    /// let mv: Move = "e7e8q".parse().unwrap();
+    /// assert_eq!(mv.to_u16(), 0b0101_111100_110100);
+    /// assert_eq!(Move::try_from_u16(mv.to_u16()), Some(mv));
+    /// ```
+    pub const fn to_u16(self) -> u16 {
+        let promotion = match self.promotion {
+            Some(piece) => piece as u16 + 1,
+            None => 0
+        };
+        self.from as u16 | (self.to as u16) << 6 | promotion << 12
+    }
+
+    /// Unpack a move encoded with [`Move::to_u16`].
+    /// Returns [`None`] for [`Move::NULL_ENCODING`] and any other value that no move encodes to.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess_types::*;
+    /// for &from in &Square::ALL {
+    ///     for &to in &Square::ALL {
+    ///         let promotions = Piece::ALL.iter().copied().map(Some).chain([None]);
+    ///         for promotion in promotions {
+    ///             let mv = Move { from, to, promotion };
+    ///             assert_eq!(Move::try_from_u16(mv.to_u16()), Some(mv));
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(Move::try_from_u16(Move::NULL_ENCODING), None);
+    /// ```
+    pub const fn try_from_u16(bits: u16) -> Option<Move> {
+        let from = Square::index_const((bits & 0b111111) as usize);
+        let to = Square::index_const((bits >> 6 & 0b111111) as usize);
+        let promotion = match bits >> 12 {
+            0 => None,
+            index => match Piece::try_index(index as usize - 1) {
+                Some(piece) => Some(piece),
+                None => return None
+            }
+        };
+        Some(Move {
+            from,
+            to,
+            promotion
+        })
+    }
+
+    /// Unpack a move encoded with [`Move::to_u16`].
+    /// # Panics
+    /// Panic if no move encodes to `bits`, including [`Move::NULL_ENCODING`].
+    /// See [`Move::try_from_u16`] for a non-panicking variant.
+    /// # Examples
+    /// ```
+    /// # use cozy_chess_types::*;
+    /// let mv = Move::from_u16(0b0000_011100_001100);
+    /// assert_eq!(mv, "e2e4".parse().unwrap());
+    /// ```
+    pub fn from_u16(bits: u16) -> Move {
+        Self::try_from_u16(bits).unwrap_or_else(|| panic!("Invalid move encoding {:#06x}.", bits))
+    }
+}
+
 /// An error while parsing a [`Move`].
 #[derive(Debug, Clone, Copy)]
 pub enum MoveParseError {
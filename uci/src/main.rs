@@ -0,0 +1,317 @@
+use std::io::BufRead;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use cozy_chess::*;
+
+use glowfish_engine::*;
+
+const MOVE_OVERHEAD: u64 = 50;
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+// 512 KiB. Unlike the cart, there's no need to be frugal here.
+const TT_SIZE: usize = 1 << 16;
+
+type Tt = TranspositionTable<TT_SIZE>;
+
+const FEATURE_OPTIONS: [&str; 5] = [
+    "NullMovePruning",
+    "LateMoveReductions",
+    "FutilityPruning",
+    "CheckExtensions",
+    "PrincipalVariationSearch"
+];
+
+#[derive(Debug, Default)]
+struct GoOptions {
+    wtime: Option<u64>,
+    btime: Option<u64>,
+    winc: Option<u64>,
+    binc: Option<u64>,
+    movestogo: Option<u64>,
+    movetime: Option<u64>,
+    depth: Option<u8>,
+    nodes: Option<u64>,
+    infinite: bool
+}
+
+impl GoOptions {
+    fn parse(mut tokens: std::str::SplitAsciiWhitespace) -> Self {
+        let mut options = Self::default();
+        while let Some(token) = tokens.next() {
+            let mut value = || tokens.next().and_then(|v| v.parse::<i64>().ok());
+            // Some GUIs send negative times when the engine is running late.
+            let mut time = || value().map(|v| v.max(0) as u64);
+            match token {
+                "wtime" => options.wtime = time(),
+                "btime" => options.btime = time(),
+                "winc" => options.winc = time(),
+                "binc" => options.binc = time(),
+                "movestogo" => options.movestogo = time(),
+                "movetime" => options.movetime = time(),
+                "depth" => options.depth = time().map(|d| d.min(MAX_DEPTH as u64) as u8),
+                "nodes" => options.nodes = time(),
+                "infinite" => options.infinite = true,
+                _ => {}
+            }
+        }
+        options
+    }
+
+    fn time_budget(&self, color: Color) -> Option<u64> {
+        if let Some(movetime) = self.movetime {
+            return Some(movetime.saturating_sub(MOVE_OVERHEAD));
+        }
+        let (time, inc) = match color {
+            Color::White => (self.wtime?, self.winc.unwrap_or(0)),
+            Color::Black => (self.btime?, self.binc.unwrap_or(0))
+        };
+        let moves_to_go = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let budget = time / moves_to_go + inc / 2;
+        Some(budget.min(time.saturating_sub(MOVE_OVERHEAD)))
+    }
+}
+
+struct UciHandler {
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    board: Board,
+    chess960: bool
+}
+
+impl SearchHandler for UciHandler {
+    fn stop(&mut self, nodes: u64) -> bool {
+        nodes.is_multiple_of(1024) && self.stop.load(Ordering::Relaxed)
+    }
+
+    fn elapsed_millis(&mut self) -> Option<u64> {
+        Some(self.start.elapsed().as_millis() as u64)
+    }
+
+    // The whole principal variation is reported, starting with the best move.
+    fn report(&mut self, result: &SearchResult) {
+        let elapsed = self.start.elapsed();
+        let nps = (result.nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64;
+        println!(
+            "info depth {} score {} nodes {} nps {} time {} pv {}",
+            result.depth,
+            format_score(result.value),
+            result.nodes,
+            nps,
+            elapsed.as_millis(),
+            format_pv(&self.board, &result.pv, self.chess960)
+        );
+    }
+}
+
+fn format_move(board: &Board, mv: Move, chess960: bool) -> String {
+    if chess960 {
+        mv.to_string()
+    } else {
+        board.to_standard_castle(mv).to_string()
+    }
+}
+
+fn format_pv(board: &Board, pv: &[Move], chess960: bool) -> String {
+    let mut board = board.clone();
+    let mut moves = Vec::new();
+    for &mv in pv {
+        moves.push(format_move(&board, mv, chess960));
+        board.play_unchecked(mv);
+    }
+    moves.join(" ")
+}
+
+fn format_score(value: i16) -> String {
+    let mate_plies = MATE_VALUE - value.abs();
+    if mate_plies <= MAX_PLY as i16 {
+        let mate_moves = (mate_plies + 1) / 2;
+        format!("mate {}", if value > 0 { mate_moves } else { -mate_moves })
+    } else {
+        format!("cp {}", value)
+    }
+}
+
+fn run_search(
+    game: ChessGame,
+    limits: SearchLimits,
+    tt: &mut Tt,
+    stop: Arc<AtomicBool>,
+    infinite: bool,
+    chess960: bool
+) {
+    let mut handler = UciHandler {
+        stop,
+        start: Instant::now(),
+        board: game.board().clone(),
+        chess960
+    };
+    let best_move = search(&game, limits, tt, &mut handler).map(|result| result.mv);
+    // The GUI must see "bestmove" only after "stop" in infinite mode.
+    while infinite && !handler.stop.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    match best_move {
+        Some(mv) => println!("bestmove {}", format_move(game.board(), mv, chess960)),
+        None => println!("bestmove 0000")
+    }
+}
+
+struct UciEngine {
+    game: ChessGame,
+    rng: Rng,
+    own_book: bool,
+    max_depth: u8,
+    features: SearchFeatures,
+    chess960: bool,
+    // Moved into the search thread while searching.
+    tt: Option<Box<Tt>>,
+    search: Option<(Arc<AtomicBool>, JoinHandle<Box<Tt>>)>
+}
+
+impl UciEngine {
+    fn new() -> Self {
+        Self {
+            game: ChessGame::new(),
+            rng: Rng::new(),
+            own_book: true,
+            max_depth: MAX_DEPTH,
+            features: SearchFeatures::ALL,
+            chess960: false,
+            tt: Some(Box::default()),
+            search: None
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some((stop, handle)) = self.search.take() {
+            stop.store(true, Ordering::Relaxed);
+            self.tt = Some(handle.join().unwrap());
+        }
+    }
+
+    fn position(&mut self, mut tokens: std::str::SplitAsciiWhitespace) {
+        let board = match tokens.next() {
+            Some("startpos") => {
+                tokens.next();
+                Board::default()
+            }
+            Some("fen") => {
+                let fen = tokens.by_ref()
+                    .take_while(|&token| token != "moves")
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                match Board::from_fen(&fen, false).or_else(|_| Board::from_fen(&fen, true)) {
+                    Ok(board) => board,
+                    Err(err) => {
+                        println!("info string invalid fen: {:?}", err);
+                        return;
+                    }
+                }
+            }
+            _ => return
+        };
+        self.game = ChessGame::from_board(board);
+        for mv in tokens {
+            let legal = self.game.board()
+                .parse_move(mv)
+                .is_ok_and(|mv| self.game.try_play(mv));
+            if !legal {
+                println!("info string illegal move {}", mv);
+                return;
+            }
+        }
+    }
+
+    fn set_option(&mut self, command: &str) {
+        let command = command.trim_start_matches("setoption").trim();
+        let (name, value) = match command.split_once(" value ") {
+            Some((name, value)) => (name, value.trim()),
+            None => (command, "")
+        };
+        match name.trim_start_matches("name").trim() {
+            "OwnBook" => self.own_book = value == "true",
+            "Depth" => if let Ok(depth) = value.parse::<u8>() {
+                self.max_depth = depth.clamp(1, MAX_DEPTH);
+            }
+            "UCI_Chess960" => self.chess960 = value == "true",
+            // Mostly for measuring each technique's effect in self-play.
+            "NullMovePruning" => self.features.null_move_pruning = value == "true",
+            "LateMoveReductions" => self.features.late_move_reductions = value == "true",
+            "FutilityPruning" => self.features.futility_pruning = value == "true",
+            "CheckExtensions" => self.features.check_extensions = value == "true",
+            "PrincipalVariationSearch" => self.features.principal_variation_search = value == "true",
+            name => println!("info string unknown option {}", name)
+        }
+    }
+
+    fn go(&mut self, tokens: std::str::SplitAsciiWhitespace) {
+        self.stop();
+        let options = GoOptions::parse(tokens);
+        // Infinite searches must not answer before "stop", so they skip the book.
+        if self.own_book && !options.infinite {
+            if let Some(mv) = book_move(&self.game, self.rng.next_u64()) {
+                println!("bestmove {}", format_move(self.game.board(), mv, self.chess960));
+                return;
+            }
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let limits = SearchLimits {
+            max_depth: options.depth.unwrap_or(self.max_depth).max(1),
+            max_nodes: options.nodes,
+            max_millis: options.time_budget(self.game.board().side_to_move()),
+            features: self.features,
+            ..SearchLimits::default()
+        };
+        let game = self.game.clone();
+        let chess960 = self.chess960;
+        let search_stop = stop.clone();
+        let mut tt = self.tt.take().unwrap();
+        let handle = std::thread::spawn(move || {
+            run_search(game, limits, &mut tt, search_stop, options.infinite, chess960);
+            tt
+        });
+        self.search = Some((stop, handle));
+    }
+}
+
+fn main() {
+    let mut engine = UciEngine::new();
+    for line in std::io::stdin().lock().lines() {
+        let line = line.unwrap();
+        let mut tokens = line.split_ascii_whitespace();
+        match tokens.next() {
+            Some("uci") => {
+                println!("id name Glowfish {}", env!("CARGO_PKG_VERSION"));
+                println!("id author analog-hors");
+                println!("option name OwnBook type check default true");
+                println!("option name Depth type spin default {0} min 1 max {0}", MAX_DEPTH);
+                println!("option name UCI_Chess960 type check default false");
+                for feature in FEATURE_OPTIONS {
+                    println!("option name {} type check default true", feature);
+                }
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("ucinewgame") => {
+                engine.stop();
+                engine.game = ChessGame::new();
+                if let Some(tt) = &mut engine.tt {
+                    tt.clear();
+                }
+            }
+            Some("position") => {
+                engine.stop();
+                engine.position(tokens);
+            }
+            Some("setoption") => engine.set_option(&line),
+            Some("go") => engine.go(tokens),
+            Some("stop") => engine.stop(),
+            Some("quit") => break,
+            _ => {}
+        }
+    }
+    engine.stop();
+}